@@ -0,0 +1,202 @@
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// What the container runtime wrote along with the line logged inside it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ContainerMeta {
+  /// `stdout` or `stderr`.
+  pub stream: String,
+  pub time: String,
+}
+
+/// A line as written by a container runtime: the line the application logged,
+/// with the stream and the time it was written at.
+#[derive(Debug, PartialEq)]
+pub struct ContainerLine {
+  pub payload: String,
+  /// `stdout` or `stderr`.
+  pub stream: String,
+  pub time: String,
+  // Long lines are split by the runtime. Every part but the last is partial.
+  partial: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Unwrapped {
+  Plain(String),
+  Container(ContainerLine),
+}
+
+impl ContainerLine {
+  pub fn parse(line: &str) -> Option<ContainerLine> {
+    ContainerLine::parse_docker(line).or_else(|| ContainerLine::parse_cri(line))
+  }
+
+  // Docker's json-file driver: `{"log":"...\n","stream":"stdout","time":"..."}`.
+  // The log only lacks its final newline when it is partial.
+  fn parse_docker(line: &str) -> Option<ContainerLine> {
+    if !line.starts_with('{') || !line.contains("\"log\"") {
+      return None;
+    }
+    let entry = serde_json::from_str::<Value>(line).ok()?;
+    let text = |key| entry.get(key).and_then(Value::as_str);
+    let log = text("log")?;
+    let payload = log.strip_suffix('\n');
+    Some(ContainerLine {
+      payload: payload.unwrap_or(log).trim_end_matches('\r').to_string(),
+      stream: text("stream")?.to_string(),
+      time: text("time")?.to_string(),
+      partial: payload.is_none(),
+    })
+  }
+
+  // containerd and CRI-O: `<time> <stream> <F|P> <log>`, where `P` marks a
+  // partial line.
+  fn parse_cri(line: &str) -> Option<ContainerLine> {
+    lazy_static! {
+      static ref CRI_REGEX: Regex =
+        Regex::new(r"^(\d{4}-\d{2}-\d{2}T\S+) (stdout|stderr) ([FP])(?: (.*))?$").unwrap();
+    }
+    let caps = CRI_REGEX.captures(line)?;
+    Some(ContainerLine {
+      payload: caps.get(4).map_or("", |m| m.as_str()).to_string(),
+      stream: caps.get(2).unwrap().as_str().to_string(),
+      time: caps.get(1).unwrap().as_str().to_string(),
+      partial: caps.get(3).unwrap().as_str() == "P",
+    })
+  }
+}
+
+/// Takes the lines off their container runtime envelope, and puts back
+/// together the lines that the runtime split.
+#[derive(Default)]
+pub struct Unwrapper {
+  // The parts read so far, by stream.
+  partials: HashMap<String, ContainerLine>,
+}
+
+impl Unwrapper {
+  /// `None` while the line is partial: it comes with its last part.
+  pub fn unwrap(&mut self, line: String) -> Option<Unwrapped> {
+    let part = match ContainerLine::parse(&line) {
+      None => return Some(Unwrapped::Plain(line)),
+      Some(part) => part,
+    };
+    let whole = match self.partials.remove(&part.stream) {
+      None => part,
+      Some(mut whole) => {
+        whole.payload.push_str(&part.payload);
+        whole.partial = part.partial;
+        whole
+      }
+    };
+    if whole.partial {
+      self.partials.insert(whole.stream.clone(), whole);
+      None
+    } else {
+      Some(Unwrapped::Container(whole))
+    }
+  }
+
+  /// The partial lines left when the input ends.
+  pub fn flush(&mut self) -> Option<Unwrapped> {
+    let stream = self.partials.keys().next()?.clone();
+    self.partials.remove(&stream).map(|whole| {
+      Unwrapped::Container(ContainerLine {
+        partial: false,
+        ..whole
+      })
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn container(payload: &str, stream: &str, time: &str) -> Option<Unwrapped> {
+    Some(Unwrapped::Container(ContainerLine {
+      payload: payload.to_string(),
+      stream: stream.to_string(),
+      time: time.to_string(),
+      partial: false,
+    }))
+  }
+
+  #[test]
+  fn unwraps_docker_lines() {
+    let mut unwrapper = Unwrapper::default();
+    assert_eq!(
+      unwrapper.unwrap(
+        r#"{"log":"{\"level\":\"info\",\"msg\":\"started\"}\n","stream":"stdout","time":"2024-01-01T00:00:00.123Z"}"#
+          .to_string()
+      ),
+      container(
+        r#"{"level":"info","msg":"started"}"#,
+        "stdout",
+        "2024-01-01T00:00:00.123Z"
+      )
+    );
+    assert_eq!(
+      unwrapper.unwrap(r#"{"log":"oops\r\n","stream":"stderr","time":"t"}"#.to_string()),
+      container("oops", "stderr", "t")
+    );
+    // Not an envelope: other keys, or the log isn't text.
+    let plain = r#"{"log":{"level":"info"},"stream":"stdout","time":"t"}"#;
+    assert_eq!(
+      unwrapper.unwrap(plain.to_string()),
+      Some(Unwrapped::Plain(plain.to_string()))
+    );
+    let plain = r#"{"log":"x\n","time":"t"}"#;
+    assert_eq!(
+      unwrapper.unwrap(plain.to_string()),
+      Some(Unwrapped::Plain(plain.to_string()))
+    );
+  }
+
+  #[test]
+  fn unwraps_cri_lines() {
+    let mut unwrapper = Unwrapper::default();
+    assert_eq!(
+      unwrapper.unwrap(r#"2024-01-01T00:00:00.5Z stdout F {"level":"info"}"#.to_string()),
+      container(r#"{"level":"info"}"#, "stdout", "2024-01-01T00:00:00.5Z")
+    );
+    assert_eq!(
+      unwrapper.unwrap("2024-01-01T00:00:00Z stderr F".to_string()),
+      container("", "stderr", "2024-01-01T00:00:00Z")
+    );
+    assert_eq!(
+      unwrapper.unwrap("2024-01-01 stdout F x".to_string()),
+      Some(Unwrapped::Plain("2024-01-01 stdout F x".to_string()))
+    );
+  }
+
+  #[test]
+  fn reassembles_partial_lines() {
+    let mut unwrapper = Unwrapper::default();
+    assert_eq!(
+      unwrapper.unwrap(r#"2024-01-01T00:00:00Z stdout P {"msg":"#.to_string()),
+      None
+    );
+    assert_eq!(
+      unwrapper.unwrap("2024-01-01T00:00:01Z stderr F error".to_string()),
+      container("error", "stderr", "2024-01-01T00:00:01Z")
+    );
+    assert_eq!(
+      unwrapper.unwrap(r#"2024-01-01T00:00:02Z stdout P "long"#.to_string()),
+      None
+    );
+    assert_eq!(
+      unwrapper.unwrap(r#"2024-01-01T00:00:03Z stdout F  line"}"#.to_string()),
+      container(r#"{"msg":"long line"}"#, "stdout", "2024-01-01T00:00:00Z")
+    );
+
+    assert_eq!(
+      unwrapper.unwrap(r#"{"log":"cut","stream":"stdout","time":"t1"}"#.to_string()),
+      None
+    );
+    assert_eq!(unwrapper.flush(), container("cut", "stdout", "t1"));
+    assert_eq!(unwrapper.flush(), None);
+  }
+}
@@ -0,0 +1,426 @@
+use crate::filter::Filter;
+use serde_json::Value;
+use std::iter::Peekable;
+use std::str::FromStr;
+
+/// A `--where` expression: filters combined with `and`, `or`, `not` and
+/// parentheses, e.g. `level=error or (level=warn and module=+Kafka)`.
+///
+/// `not` binds tighter than `and`, which binds tighter than `or`. Filters
+/// containing spaces or parentheses can be quoted: `message~"node (\d+)"`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expression {
+  Filter(Filter),
+  Not(Box<Expression>),
+  And(Box<Expression>, Box<Expression>),
+  Or(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+  pub fn passes(&self, entry: &Value) -> bool {
+    match self {
+      Expression::Filter(filter) => filter.passes(entry),
+      Expression::Not(expression) => !expression.passes(entry),
+      Expression::And(left, right) => left.passes(entry) && right.passes(entry),
+      Expression::Or(left, right) => left.passes(entry) || right.passes(entry),
+    }
+  }
+
+  /// Applies the filters searching text to a whole line that isn't JSON (see
+  /// `Filter::passes_text`), the others are left out of the expression.
+  /// `None` when none of them search text.
+  pub fn passes_text(&self, line: &str) -> Option<bool> {
+    let combine = |left: &Expression, right: &Expression, both: fn(bool, bool) -> bool| match (
+      left.passes_text(line),
+      right.passes_text(line),
+    ) {
+      (Some(left), Some(right)) => Some(both(left, right)),
+      (one, None) | (None, one) => one,
+    };
+    match self {
+      Expression::Filter(filter) => filter.passes_text(line),
+      Expression::Not(expression) => expression.passes_text(line).map(|passes| !passes),
+      Expression::And(left, right) => combine(left, right, |left, right| left && right),
+      Expression::Or(left, right) => combine(left, right, |left, right| left || right),
+    }
+  }
+}
+
+impl FromStr for Expression {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let error_at = |(message, position): (String, usize)| {
+      format!("{message}\n  {text}\n  {}^", " ".repeat(position))
+    };
+    let tokens = tokenize(text).map_err(error_at)?;
+    let mut parser = Parser {
+      tokens: &tokens,
+      next: 0,
+      end: text.chars().count(),
+    };
+    let expression = parser.parse_or().map_err(error_at)?;
+    match parser.peek() {
+      None => Ok(expression),
+      Some(token) => Err(error_at((
+        format!("Unexpected {}", token.kind.describe()),
+        token.position,
+      ))),
+    }
+  }
+}
+
+// An error message with the position (in characters) it refers to.
+type ParseError = (String, usize);
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+  Open,
+  Close,
+  And,
+  Or,
+  Not,
+  Term(String),
+}
+
+impl TokenKind {
+  fn describe(&self) -> String {
+    match self {
+      TokenKind::Open => "'('".to_string(),
+      TokenKind::Close => "')'".to_string(),
+      TokenKind::And => "'and'".to_string(),
+      TokenKind::Or => "'or'".to_string(),
+      TokenKind::Not => "'not'".to_string(),
+      TokenKind::Term(term) => format!("'{term}'"),
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+struct Token {
+  kind: TokenKind,
+  position: usize,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+  let mut tokens = Vec::new();
+  let mut chars = text.chars().enumerate().peekable();
+
+  while let Some(&(position, c)) = chars.peek() {
+    match c {
+      c if c.is_whitespace() => {
+        chars.next();
+      }
+      '(' | ')' => {
+        chars.next();
+        let kind = if c == '(' {
+          TokenKind::Open
+        } else {
+          TokenKind::Close
+        };
+        tokens.push(Token { kind, position });
+      }
+      _ => {
+        let mut term = String::new();
+        let mut quoted = false;
+        while let Some(&(quote_position, c)) = chars.peek() {
+          if c.is_whitespace() || c == '(' || c == ')' {
+            break;
+          }
+          chars.next();
+          if c != '"' {
+            term.push(c);
+            continue;
+          }
+          quoted = true;
+          loop {
+            match chars.next() {
+              None => return Err(("Unclosed quote".to_string(), quote_position)),
+              Some((_, '"')) => break,
+              Some((_, '\\')) if chars.peek().is_some_and(|&(_, next)| next == '"') => {
+                term.push('"');
+                chars.next();
+              }
+              Some((_, other)) => term.push(other),
+            }
+          }
+        }
+        let kind = match term.to_lowercase().as_str() {
+          "and" if !quoted => TokenKind::And,
+          "or" if !quoted => TokenKind::Or,
+          "not" if !quoted => TokenKind::Not,
+          _ => match in_list(&mut chars)? {
+            Some(list) => TokenKind::Term(format!("{term} in ({list})")),
+            None => TokenKind::Term(term),
+          },
+        };
+        tokens.push(Token { kind, position });
+      }
+    }
+  }
+  Ok(tokens)
+}
+
+// The `in (a, b)` following a term, which is part of the same filter.
+fn in_list<I>(chars: &mut Peekable<I>) -> Result<Option<String>, ParseError>
+where
+  I: Iterator<Item = (usize, char)> + Clone,
+{
+  let mut ahead = chars.clone();
+  let skip_whitespace = |ahead: &mut Peekable<I>| {
+    while ahead.next_if(|&(_, c)| c.is_whitespace()).is_some() {}
+  };
+  skip_whitespace(&mut ahead);
+  let keyword: String = (0..2)
+    .filter_map(|_| ahead.next())
+    .map(|(_, c)| c)
+    .collect();
+  let before_list = ahead.peek().map(|&(_, c)| c);
+  if !keyword.eq_ignore_ascii_case("in")
+    || before_list.is_some_and(|c| c != '(' && !c.is_whitespace())
+  {
+    return Ok(None);
+  }
+  skip_whitespace(&mut ahead);
+  let open_position = match ahead.next() {
+    Some((position, '(')) => position,
+    _ => return Ok(None),
+  };
+
+  let mut list = String::new();
+  let mut quoted = false;
+  loop {
+    match ahead.next() {
+      None => return Err(("Unclosed '(' of the 'in' list".to_string(), open_position)),
+      Some((_, ')')) if !quoted => break,
+      Some((_, c)) => {
+        quoted ^= c == '"';
+        list.push(c);
+      }
+    }
+  }
+  *chars = ahead;
+  Ok(Some(list))
+}
+
+struct Parser<'a> {
+  tokens: &'a [Token],
+  next: usize,
+  // Position reported when the expression ends too early.
+  end: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn peek(&self) -> Option<&'a Token> {
+    self.tokens.get(self.next)
+  }
+
+  fn advance(&mut self) -> Option<&'a Token> {
+    let token = self.tokens.get(self.next);
+    self.next += 1;
+    token
+  }
+
+  fn parse_or(&mut self) -> Result<Expression, ParseError> {
+    let mut expression = self.parse_and()?;
+    while self.peek().is_some_and(|token| token.kind == TokenKind::Or) {
+      self.advance();
+      expression = Expression::Or(Box::new(expression), Box::new(self.parse_and()?));
+    }
+    Ok(expression)
+  }
+
+  fn parse_and(&mut self) -> Result<Expression, ParseError> {
+    let mut expression = self.parse_not()?;
+    while self
+      .peek()
+      .is_some_and(|token| token.kind == TokenKind::And)
+    {
+      self.advance();
+      expression = Expression::And(Box::new(expression), Box::new(self.parse_not()?));
+    }
+    Ok(expression)
+  }
+
+  fn parse_not(&mut self) -> Result<Expression, ParseError> {
+    if self
+      .peek()
+      .is_some_and(|token| token.kind == TokenKind::Not)
+    {
+      self.advance();
+      return Ok(Expression::Not(Box::new(self.parse_not()?)));
+    }
+    self.parse_primary()
+  }
+
+  fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+    let token = match self.advance() {
+      None => return Err(("Expected a filter or '('".to_string(), self.end)),
+      Some(token) => token,
+    };
+    match &token.kind {
+      TokenKind::Term(term) => term
+        .parse::<Filter>()
+        .map(Expression::Filter)
+        .map_err(|e| (e, token.position)),
+      TokenKind::Open => {
+        let expression = self.parse_or()?;
+        match self.advance() {
+          Some(Token {
+            kind: TokenKind::Close,
+            ..
+          }) => Ok(expression),
+          Some(other) => Err((
+            format!("Expected ')' but found {}", other.kind.describe()),
+            other.position,
+          )),
+          None => Err((
+            format!("Expected ')' to close the '(' at {}", token.position + 1),
+            self.end,
+          )),
+        }
+      }
+      other => Err((
+        format!("Expected a filter or '(' but found {}", other.describe()),
+        token.position,
+      )),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn filter(text: &str) -> Box<Expression> {
+    Box::new(Expression::Filter(text.parse().unwrap()))
+  }
+
+  fn parse(text: &str) -> Expression {
+    text.parse().unwrap()
+  }
+
+  #[test]
+  fn test_parse_precedence() {
+    assert_eq!(
+      parse("level=error or level=warn and module=+Kafka"),
+      Expression::Or(
+        filter("level=error"),
+        Box::new(Expression::And(
+          filter("level=warn"),
+          filter("module=+Kafka")
+        ))
+      )
+    );
+    assert_eq!(
+      parse("(level=error OR level=warn) AND NOT module=+Kafka"),
+      Expression::And(
+        Box::new(Expression::Or(filter("level=error"), filter("level=warn"))),
+        Box::new(Expression::Not(filter("module=+Kafka")))
+      )
+    );
+    assert_eq!(
+      parse("not not app=drive"),
+      Expression::Not(Box::new(Expression::Not(filter("app=drive"))))
+    );
+  }
+
+  #[test]
+  fn test_parse_quoted_filters() {
+    assert_eq!(
+      parse(r#"message~"node (\d+)" and "message=+and or""#),
+      Expression::And(filter(r"message~node (\d+)"), filter("message=+and or"))
+    );
+    assert_eq!(
+      parse(r#"message="say \"hi\"""#),
+      *filter(r#"message=say "hi""#)
+    );
+  }
+
+  #[test]
+  fn test_parse_errors_point_at_the_problem() {
+    assert_eq!(
+      "level=error or (level=warn".parse::<Expression>().unwrap_err(),
+      "Expected ')' to close the '(' at 16\n  level=error or (level=warn\n                            ^"
+    );
+    assert_eq!(
+      "level=error and or level=warn".parse::<Expression>().unwrap_err(),
+      "Expected a filter or '(' but found 'or'\n  level=error and or level=warn\n                  ^"
+    );
+    assert_eq!(
+      "level=error level=warn".parse::<Expression>().unwrap_err(),
+      "Unexpected 'level=warn'\n  level=error level=warn\n              ^"
+    );
+    assert_eq!(
+      "level=error or message~\"("
+        .parse::<Expression>()
+        .unwrap_err(),
+      "Unclosed quote\n  level=error or message~\"(\n                         ^"
+    );
+    assert!("level=error or message~\"(\""
+      .parse::<Expression>()
+      .unwrap_err()
+      .starts_with("Invalid regex in filter 'message~('"));
+    assert!("".parse::<Expression>().is_err());
+    assert!("()".parse::<Expression>().is_err());
+    assert!("a=b)".parse::<Expression>().is_err());
+  }
+
+  #[test]
+  fn test_parse_in_lists() {
+    assert_eq!(
+      parse("level:i in (error, fatal) and not app IN (\"a (b)\", c)"),
+      Expression::And(
+        filter("level:i in (error, fatal)"),
+        Box::new(Expression::Not(filter("app in (\"a (b)\", c)")))
+      )
+    );
+    // Only an `in` keyword followed by a list.
+    assert_eq!(
+      parse("app=a or (in=b and index=c)"),
+      Expression::Or(
+        filter("app=a"),
+        Box::new(Expression::And(filter("in=b"), filter("index=c")))
+      )
+    );
+    assert_eq!(
+      "level in (error, fatal".parse::<Expression>().unwrap_err(),
+      "Unclosed '(' of the 'in' list\n  level in (error, fatal\n           ^"
+    );
+  }
+
+  #[test]
+  fn test_expression_passes() {
+    let line = json!({ "level": "warn", "module": "Elixir.Kafka.Consumer" });
+    assert!(parse("level=error or (level=warn and module=+Kafka)").passes(&line));
+    assert!(!parse("level=error or (level=warn and module=+Flink)").passes(&line));
+    assert!(parse("not level=error").passes(&line));
+    assert!(!parse("not (level=error or level=warn)").passes(&line));
+    assert!(parse("level=warn and not module=+Flink").passes(&line));
+  }
+
+  #[test]
+  fn test_expression_passes_text() {
+    let line = "Starting Kafka consumer on node 12";
+    assert_eq!(
+      parse("level=error or message=+Kafka").passes_text(line),
+      Some(true)
+    );
+    assert_eq!(
+      parse("level=error and message=+Flink").passes_text(line),
+      Some(false)
+    );
+    assert_eq!(
+      parse(r#"not message~"node \d+""#).passes_text(line),
+      Some(false)
+    );
+    assert_eq!(
+      parse("message=+Flink or message=+node").passes_text(line),
+      Some(true)
+    );
+    assert_eq!(
+      parse("level=error or not has:stack").passes_text(line),
+      None
+    );
+  }
+}
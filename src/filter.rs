@@ -1,99 +1,372 @@
+use crate::path::FieldPath;
 use regex::Regex;
 use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::str::FromStr;
 
-pub fn parse_filters(unparsed_filters: Vec<&str>) -> Vec<Filter> {
-  unparsed_filters.iter().copied().map(Filter::from).collect()
+pub fn parse_filters(unparsed_filters: Vec<&str>) -> Result<Vec<Filter>, String> {
+  unparsed_filters.iter().map(|text| text.parse()).collect()
 }
 
 pub fn passes_filters(filters: &[Filter], entry: &Value) -> bool {
   filters.iter().all(|f| f.passes(entry))
 }
 
-#[derive(Debug, PartialEq)]
+/// For lines that aren't JSON: only the filters searching text are applied.
+pub fn passes_text_filters(filters: &[Filter], line: &str) -> bool {
+  filters.iter().all(|f| f.passes_text(line).unwrap_or(true))
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum FilterKind {
   Equals,
+  NotEquals,
+  Greater,
+  GreaterOrEqual,
+  Less,
+  LessOrEqual,
   Contains,
   NotContains,
+  Matches(Pattern),
+  NotMatches(Pattern),
+  Exists,
+  Missing,
+  In(Vec<String>),
+}
+
+impl FilterKind {
+  // The negative kinds hold for a field that isn't there: a line without
+  // `module` doesn't contain "Kafka" in it.
+  fn passes_when_missing(&self) -> bool {
+    matches!(
+      self,
+      FilterKind::NotEquals
+        | FilterKind::NotContains
+        | FilterKind::NotMatches(_)
+        | FilterKind::Missing
+    )
+  }
 }
 
-#[derive(Debug, PartialEq)]
+/// A compiled regex, compared by its source text.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl PartialEq for Pattern {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.as_str() == other.0.as_str()
+  }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Filter {
-  key: String,
+  key: FieldPath,
   kind: FilterKind,
   value: String,
+  // Set with `:i` after the key. The expected values are then lowercase.
+  ignore_case: bool,
+}
+
+// Strings are compared as they are, numbers, booleans and null by their JSON
+// meaning (`664` equals `664.0`), objects and arrays by their JSON text.
+fn json_equals(value: &Value, expected: &str) -> bool {
+  match value {
+    Value::String(text) => text == expected,
+    Value::Number(number) => {
+      number.to_string() == expected
+        || match (number.as_f64(), expected.trim().parse::<f64>()) {
+          (Some(actual), Ok(expected)) => actual == expected,
+          _ => false,
+        }
+    }
+    Value::Bool(boolean) => expected.trim().parse::<bool>() == Ok(*boolean),
+    Value::Null => expected.trim() == "null",
+    other => rendered(other) == expected,
+  }
+}
+
+// Numeric when both sides are numbers (or strings holding numbers, like
+// `"status": "500"`), lexicographic otherwise.
+fn json_compare(value: &Value, expected: &str) -> Option<Ordering> {
+  let actual_number = match value {
+    Value::Number(number) => number.as_f64(),
+    Value::String(text) => parse_number(text),
+    _ => None,
+  };
+  match (actual_number, parse_number(expected)) {
+    (Some(actual), Some(expected)) => actual.partial_cmp(&expected),
+    _ => Some(rendered(value).as_ref().cmp(expected)),
+  }
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+  // `f64::from_str` also accepts "inf" and "NaN", which aren't numbers here.
+  text
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .filter(|number| number.is_finite())
+}
+
+// The text that `Contains`, `Matches` and their negations search in.
+fn rendered(value: &Value) -> Cow<'_, str> {
+  match value {
+    Value::String(text) => Cow::Borrowed(text),
+    other => Cow::Owned(other.to_string()),
+  }
 }
 
 impl Filter {
-  fn passes(&self, entry: &Value) -> bool {
-    let possible_value = entry.get(&self.key);
-    if let Some(some_value) = possible_value {
-      let value = some_value.as_str().unwrap();
-      match self.kind {
-        FilterKind::Equals => value == self.value,
-        FilterKind::Contains => value.contains(self.value.as_str()),
-        FilterKind::NotContains => !value.contains(self.value.as_str()),
+  pub fn passes(&self, entry: &Value) -> bool {
+    match self.key.get(entry) {
+      None => self.kind.passes_when_missing(),
+      Some(value) => match &self.kind {
+        FilterKind::Equals => self.value_equals(value, &self.value),
+        FilterKind::NotEquals => !self.value_equals(value, &self.value),
+        FilterKind::Greater => json_compare(value, &self.value) == Some(Ordering::Greater),
+        FilterKind::GreaterOrEqual => matches!(
+          json_compare(value, &self.value),
+          Some(Ordering::Greater | Ordering::Equal)
+        ),
+        FilterKind::Less => json_compare(value, &self.value) == Some(Ordering::Less),
+        FilterKind::LessOrEqual => matches!(
+          json_compare(value, &self.value),
+          Some(Ordering::Less | Ordering::Equal)
+        ),
+        FilterKind::Contains => self.value_contains(value),
+        FilterKind::NotContains => !self.value_contains(value),
+        FilterKind::Matches(pattern) => pattern.0.is_match(&rendered(value)),
+        FilterKind::NotMatches(pattern) => !pattern.0.is_match(&rendered(value)),
+        FilterKind::Exists => true,
+        FilterKind::Missing => false,
+        FilterKind::In(values) => values
+          .iter()
+          .any(|expected| self.value_equals(value, expected)),
+      },
+    }
+  }
+
+  fn value_equals(&self, value: &Value, expected: &str) -> bool {
+    match value {
+      Value::String(_) | Value::Array(_) | Value::Object(_) if self.ignore_case => {
+        rendered(value).to_lowercase() == expected
       }
-    } else {
-      false
+      _ => json_equals(value, expected),
     }
   }
 
-  fn equals(key: &str, value: &str) -> Self {
-    Filter {
-      kind: FilterKind::Equals,
-      key: key.to_string(),
-      value: value.to_string(),
+  /// Applies the filter to a whole line that isn't JSON, when it searches
+  /// text (`=+`, `=^`, `~`, `!~`). The field is then ignored.
+  pub fn passes_text(&self, line: &str) -> Option<bool> {
+    match &self.kind {
+      FilterKind::Contains => Some(self.text_contains(line)),
+      FilterKind::NotContains => Some(!self.text_contains(line)),
+      FilterKind::Matches(pattern) => Some(pattern.0.is_match(line)),
+      FilterKind::NotMatches(pattern) => Some(!pattern.0.is_match(line)),
+      _ => None,
     }
   }
 
-  fn contains(key: &str, value: &str) -> Self {
-    Filter {
-      kind: FilterKind::Contains,
-      key: key.to_string(),
-      value: value.to_string(),
+  fn value_contains(&self, value: &Value) -> bool {
+    self.text_contains(&rendered(value))
+  }
+
+  fn text_contains(&self, text: &str) -> bool {
+    if self.ignore_case {
+      text.to_lowercase().contains(self.value.as_str())
+    } else {
+      text.contains(self.value.as_str())
     }
   }
-  fn not_contains(key: &str, value: &str) -> Self {
-    Filter {
-      kind: FilterKind::NotContains,
-      key: key.to_string(),
+
+  fn new(key: &str, kind: FilterKind, value: &str) -> Result<Self, String> {
+    Ok(Filter {
+      key: FieldPath::parse(key)?,
+      kind,
       value: value.to_string(),
-    }
+      ignore_case: false,
+    })
   }
 
-  fn from(text: &str) -> Filter {
+  fn ignoring_case(self) -> Result<Self, String> {
+    let kind = match self.kind {
+      FilterKind::Equals
+      | FilterKind::NotEquals
+      | FilterKind::Contains
+      | FilterKind::NotContains => self.kind,
+      FilterKind::In(values) => FilterKind::In(values.iter().map(|v| v.to_lowercase()).collect()),
+      _ => {
+        return Err(
+          "':i' only applies to =, !=, =+, =^ and in (use (?i) to ignore case in a regex)"
+            .to_string(),
+        )
+      }
+    };
+    Ok(Filter {
+      kind,
+      value: self.value.to_lowercase(),
+      ignore_case: true,
+      ..self
+    })
+  }
+}
+
+impl FromStr for Filter {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
     lazy_static! {
-      static ref CONTAINS_REGEX: Regex = Regex::new(r"^([^=]+)=\+(.+)$").unwrap();
-      static ref NOT_CONTAINS_REGEX: Regex = Regex::new(r"^([^=]+)=\^(.+)$").unwrap();
-      static ref EQUALS_REGEX: Regex = Regex::new(r"^([^=]+)=(.+)").unwrap();
+      // The first operator found splits the filter: `a>b=c` is `a` greater
+      // than `b=c`, and `a!b=x` is still `a!b` equal to `x`. Operators can be
+      // kept in the key by escaping them (`a\>b=c`) or quoting the key
+      // (`["a>b"]=c`). `:i` right before the operator ignores case.
+      static ref FILTER_REGEX: Regex = Regex::new(
+        r#"^((?:\\.|\["(?:[^"\\]|\\.)*"\]|[^=\\])+?)(:i)?(=\+|=\^|=|!=|!~|~|>=|>|<=|<)(.+)$"#
+      )
+      .unwrap();
+      static ref PRESENCE_REGEX: Regex = Regex::new(r"^(has|missing):([^=~!<>]+)$").unwrap();
+      static ref IN_REGEX: Regex =
+        Regex::new(r"^([^=~!<>]+?)(:i)?\s+(?i:in)\s*\((.*)\)$").unwrap();
     }
 
-    if CONTAINS_REGEX.is_match(text) {
-      let caps = CONTAINS_REGEX.captures(text).unwrap();
-      Filter::contains(caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str())
-    } else if NOT_CONTAINS_REGEX.is_match(text) {
-      let caps = NOT_CONTAINS_REGEX.captures(text).unwrap();
-      Filter::not_contains(caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str())
-    } else if EQUALS_REGEX.is_match(text) {
-      let caps = EQUALS_REGEX.captures(text).unwrap();
-      Filter::equals(caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str())
-    } else {
-      println!("Can't parse filter: {text}");
-      panic!("Error.");
+    if let Some(caps) = IN_REGEX.captures(text) {
+      let list = caps.get(3).unwrap().as_str();
+      let values = split_list(list);
+      if values.iter().any(String::is_empty) {
+        return Err(format!(
+          "Can't parse filter '{text}': expected a list of values like (error, fatal)"
+        ));
+      }
+      let filter = Filter::new(caps.get(1).unwrap().as_str(), FilterKind::In(values), list);
+      return with_case(filter, caps.get(2).is_some())
+        .map_err(|e| format!("Can't parse filter '{text}': {e}"));
+    }
+
+    if let Some(caps) = PRESENCE_REGEX.captures(text) {
+      let kind = match caps.get(1).unwrap().as_str() {
+        "has" => FilterKind::Exists,
+        _ => FilterKind::Missing,
+      };
+      return Filter::new(caps.get(2).unwrap().as_str(), kind, "")
+        .map_err(|e| format!("Can't parse filter '{text}': {e}"));
     }
+
+    let caps = FILTER_REGEX.captures(text).ok_or_else(|| {
+      format!(
+        "Can't parse filter '{text}', expected <field>=<value>, <field>=+<value>, \
+         <field>=^<value>, <field>~<regex>, <field>!~<regex>, <field>!=<value>, \
+         <field>><value>, <field>>=<value>, <field><<value>, <field><=<value>, \
+         <field> in (<value>, ...), has:<field> or missing:<field>"
+      )
+    })?;
+    let key = caps.get(1).unwrap().as_str();
+    let value = caps.get(4).unwrap().as_str();
+
+    let kind = match caps.get(3).unwrap().as_str() {
+      "=+" => FilterKind::Contains,
+      "!=" => FilterKind::NotEquals,
+      ">" => FilterKind::Greater,
+      ">=" => FilterKind::GreaterOrEqual,
+      "<" => FilterKind::Less,
+      "<=" => FilterKind::LessOrEqual,
+      "=^" => FilterKind::NotContains,
+      "~" => FilterKind::Matches(compile(text, value)?),
+      "!~" => FilterKind::NotMatches(compile(text, value)?),
+      _ => FilterKind::Equals,
+    };
+    with_case(Filter::new(key, kind, value), caps.get(2).is_some())
+      .map_err(|e| format!("Can't parse filter '{text}': {e}"))
   }
 }
 
+fn with_case(filter: Result<Filter, String>, ignore_case: bool) -> Result<Filter, String> {
+  if ignore_case {
+    filter?.ignoring_case()
+  } else {
+    filter
+  }
+}
+
+// The values of an `in` list, which can be quoted to keep their spaces or
+// commas.
+fn split_list(list: &str) -> Vec<String> {
+  let mut values = Vec::new();
+  let mut start = 0;
+  let mut quoted = false;
+  for (index, c) in list.char_indices() {
+    match c {
+      '"' => quoted = !quoted,
+      ',' if !quoted => {
+        values.push(unquoted(&list[start..index]));
+        start = index + 1;
+      }
+      _ => (),
+    }
+  }
+  values.push(unquoted(&list[start..]));
+  values
+}
+
+fn unquoted(value: &str) -> String {
+  let value = value.trim();
+  match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+    Some(inner) if !inner.is_empty() => inner.to_string(),
+    _ => value.to_string(),
+  }
+}
+
+fn compile(text: &str, pattern: &str) -> Result<Pattern, String> {
+  Regex::new(pattern)
+    .map(Pattern)
+    .map_err(|e| format!("Invalid regex in filter '{text}': {e}"))
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  impl Filter {
+    fn equals(key: &str, value: &str) -> Self {
+      Filter::new(key, FilterKind::Equals, value).unwrap()
+    }
+
+    fn contains(key: &str, value: &str) -> Self {
+      Filter::new(key, FilterKind::Contains, value).unwrap()
+    }
+
+    fn not_contains(key: &str, value: &str) -> Self {
+      Filter::new(key, FilterKind::NotContains, value).unwrap()
+    }
+
+    fn from(text: &str) -> Self {
+      text.parse().unwrap()
+    }
+  }
+
   #[test]
   fn test_parse_filter_equal() {
     assert_eq!(
       Filter::from("mykey=m'value"),
       Filter::equals("mykey", "m'value")
     );
+    // Keys that were accepted before the other operators still are.
+    assert_eq!(Filter::from("a!b=x"), Filter::equals("a!b", "x"));
+    // The other operators split the filter where they are found, unless
+    // escaped or quoted.
+    assert_eq!(
+      Filter::from("a>b=c"),
+      Filter::new("a", FilterKind::Greater, "b=c").unwrap()
+    );
+    let line = json!({ "a>b": "c", "a~b": "d" });
+    assert!(Filter::from(r"a\>b=c").passes(&line));
+    assert!(Filter::from(r#"["a>b"]=c"#).passes(&line));
+    assert!(Filter::from(r"a\~b=d").passes(&line));
+    assert!(!Filter::from("a~b=d").passes(&line));
+    assert_eq!(
+      Filter::from("level!=info"),
+      Filter::new("level", FilterKind::NotEquals, "info").unwrap()
+    );
   }
 
   #[test]
@@ -120,7 +393,8 @@ mod tests {
         "this=that",
         "module=+Drive",
         "thing=^ploup"
-      ]),
+      ])
+      .unwrap(),
       vec![
         Filter::contains("the_key", "search_for"),
         Filter::equals("this", "that"),
@@ -130,6 +404,226 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_parse_filter_matches() {
+    let filter = Filter::from(r"message~^Timeout.*node \d+$");
+    assert_eq!(filter.key, FieldPath::parse("message").unwrap());
+    assert_eq!(
+      filter.kind,
+      FilterKind::Matches(Pattern(Regex::new(r"^Timeout.*node \d+$").unwrap()))
+    );
+
+    let filter = Filter::from("message!~a=b");
+    assert_eq!(
+      filter.kind,
+      FilterKind::NotMatches(Pattern(Regex::new("a=b").unwrap()))
+    );
+  }
+
+  #[test]
+  fn test_parse_invalid_filters() {
+    assert_eq!(
+      "message~(unclosed".parse::<Filter>().unwrap_err(),
+      "Invalid regex in filter 'message~(unclosed': regex parse error:\n    (unclosed\n    ^\nerror: unclosed group"
+    );
+    assert!("no_operator".parse::<Filter>().is_err());
+    assert!("empty_value=".parse::<Filter>().is_err());
+    assert!("a..b=c".parse::<Filter>().is_err());
+    assert!(parse_filters(vec!["app=drive", "module~["]).is_err());
+  }
+
+  #[test]
+  fn test_parse_filter_comparisons() {
+    let kind = |text: &str| Filter::from(text).kind;
+    assert_eq!(kind("thread_id>600"), FilterKind::Greater);
+    assert_eq!(kind("duration_ms>=500"), FilterKind::GreaterOrEqual);
+    assert_eq!(kind("status<500"), FilterKind::Less);
+    assert_eq!(kind("status<=499"), FilterKind::LessOrEqual);
+    assert_eq!(kind("level!=debug"), FilterKind::NotEquals);
+    assert_eq!(Filter::from("duration_ms>=500").value, "500");
+    assert_eq!(Filter::from("message=a>b").kind, FilterKind::Equals);
+  }
+
+  #[test]
+  fn filter_comparisons_on_numbers() {
+    let line = json!({ "thread_id": 664, "duration_ms": 500.0, "status": "503" });
+    assert!(Filter::from("thread_id>600").passes(&line));
+    assert!(!Filter::from("thread_id>664").passes(&line));
+    assert!(Filter::from("thread_id>=664").passes(&line));
+    assert!(Filter::from("thread_id<1000").passes(&line));
+    assert!(!Filter::from("thread_id<=663.5").passes(&line));
+    assert!(Filter::from("duration_ms>=500").passes(&line));
+    assert!(!Filter::from("duration_ms<500").passes(&line));
+    // Numeric strings compare as numbers: lexicographically "503" < "6".
+    assert!(Filter::from("status>=500").passes(&line));
+    assert!(Filter::from("status>6").passes(&line));
+    assert!(Filter::from("thread_id!=665").passes(&line));
+    assert!(!Filter::from("thread_id!=664.0").passes(&line));
+  }
+
+  #[test]
+  fn filter_comparisons_fall_back_to_text() {
+    let line = json!({
+      "timestamp": "2019-12-18T10:55:50.000345",
+      "level": "warn",
+      "thread_id": 664,
+      "ok": true
+    });
+    assert!(Filter::from("timestamp>=2019-12-18T10:55").passes(&line));
+    assert!(!Filter::from("timestamp<2019-12-18T10:55").passes(&line));
+    assert!(Filter::from("level>info").passes(&line));
+    assert!(!Filter::from("level<error").passes(&line));
+    // A number against a non-numeric value compares their text.
+    assert!(Filter::from("thread_id<abc").passes(&line));
+    assert!(Filter::from("ok>false").passes(&line));
+    // "inf" isn't taken as a number: "true" > "inf".
+    assert!(Filter::from("ok>inf").passes(&line));
+    assert!(!Filter::from("thread_id>inf").passes(&line));
+    assert!(Filter::from("level!=info").passes(&line));
+    // Negative filters hold for missing fields.
+    assert!(Filter::from("missing!=info").passes(&line));
+    assert!(!Filter::from("missing>0").passes(&line));
+  }
+
+  #[test]
+  fn test_parse_presence_filters() {
+    assert_eq!(
+      Filter::from("has:stack_trace"),
+      Filter::new("stack_trace", FilterKind::Exists, "").unwrap()
+    );
+    assert_eq!(
+      Filter::from("missing:mdc.request_id"),
+      Filter::new("mdc.request_id", FilterKind::Missing, "").unwrap()
+    );
+    // Only a prefix when the rest is a field.
+    assert_eq!(Filter::from("has:a=b"), Filter::equals("has:a", "b"));
+    assert!("has:".parse::<Filter>().is_err());
+  }
+
+  #[test]
+  fn filter_presence_passes() {
+    let line = json!({ "stack_trace": "at Foo.bar", "mdc": { "request_id": null } });
+    assert!(Filter::from("has:stack_trace").passes(&line));
+    assert!(!Filter::from("missing:stack_trace").passes(&line));
+    // A null value is there.
+    assert!(Filter::from("has:mdc.request_id").passes(&line));
+    assert!(!Filter::from("has:mdc.user_id").passes(&line));
+    assert!(Filter::from("missing:mdc.user_id").passes(&line));
+    assert!(Filter::from("missing:thrown").passes(&line));
+  }
+
+  #[test]
+  fn filter_on_missing_fields() {
+    let line = json!({ "message": "no module here" });
+    for positive in [
+      "module=Kafka",
+      "module=+Kafka",
+      "module~Kafka",
+      "module>0",
+      "module>=0",
+      "module<0",
+      "module<=0",
+    ] {
+      assert!(!Filter::from(positive).passes(&line), "{}", positive);
+    }
+    for negative in ["module!=Kafka", "module=^Kafka", "module!~Kafka"] {
+      assert!(Filter::from(negative).passes(&line), "{}", negative);
+    }
+  }
+
+  #[test]
+  fn test_parse_case_insensitive_and_in_filters() {
+    let filter = Filter::from("level:i=INFO");
+    assert_eq!(filter.key, FieldPath::parse("level").unwrap());
+    assert_eq!(
+      (filter.kind, filter.value.as_str()),
+      (FilterKind::Equals, "info")
+    );
+    assert!(Filter::from("module:i=+kafka").ignore_case);
+    assert!(!Filter::from("module=+kafka").ignore_case);
+    assert_eq!(
+      Filter::from("level in (error, fatal,critical)").kind,
+      FilterKind::In(vec!["error".into(), "fatal".into(), "critical".into()])
+    );
+    assert_eq!(
+      Filter::from(r#"tags[0]:i IN ("A, B", C)"#).kind,
+      FilterKind::In(vec!["a, b".into(), "c".into()])
+    );
+    assert!("level:i~^info".parse::<Filter>().is_err());
+    assert!("status:i>500".parse::<Filter>().is_err());
+    assert!("level in ()".parse::<Filter>().is_err());
+    assert!("level in (error,)".parse::<Filter>().is_err());
+  }
+
+  #[test]
+  fn filter_case_insensitive_passes() {
+    let line = json!({ "level": "Info", "module": "Elixir.Kafka.Consumer", "ok": true });
+    assert!(Filter::from("level:i=INFO").passes(&line));
+    assert!(!Filter::from("level=INFO").passes(&line));
+    assert!(Filter::from("level:i!=warn").passes(&line));
+    assert!(!Filter::from("level:i!=info").passes(&line));
+    assert!(Filter::from("module:i=+KAFKA").passes(&line));
+    assert!(!Filter::from("module:i=^kafka").passes(&line));
+    assert!(Filter::from("ok:i=TRUE").passes(&line));
+  }
+
+  #[test]
+  fn filter_in_passes() {
+    let line = json!({ "level": "Error", "status": 503 });
+    assert!(!Filter::from("level in (error, fatal)").passes(&line));
+    assert!(Filter::from("level in (Error, Fatal)").passes(&line));
+    assert!(Filter::from("level:i in (error, fatal)").passes(&line));
+    assert!(!Filter::from("level:i in (warn, info)").passes(&line));
+    assert!(Filter::from("status in (500, 503.0)").passes(&line));
+    assert!(!Filter::from("missing in (error)").passes(&line));
+  }
+
+  #[test]
+  fn filter_passes_text() {
+    let line = "2024-05-01 10:52:00 ERROR Timeout while waiting for node 12";
+    assert_eq!(
+      Filter::from("message=+Timeout").passes_text(line),
+      Some(true)
+    );
+    assert_eq!(Filter::from("any:i=+timeout").passes_text(line), Some(true));
+    assert_eq!(
+      Filter::from("message=^Timeout").passes_text(line),
+      Some(false)
+    );
+    assert_eq!(
+      Filter::from(r"message~node \d+$").passes_text(line),
+      Some(true)
+    );
+    assert_eq!(
+      Filter::from("message!~ERROR").passes_text(line),
+      Some(false)
+    );
+    assert_eq!(Filter::from("level=error").passes_text(line), None);
+    assert_eq!(Filter::from("has:level").passes_text(line), None);
+    assert!(passes_text_filters(
+      &parse_filters(vec!["level=info", "message=+node"]).unwrap(),
+      line
+    ));
+    assert!(!passes_text_filters(
+      &parse_filters(vec!["level=error", "message=+Kafka"]).unwrap(),
+      line
+    ));
+  }
+
+  #[test]
+  fn filter_matches_passes() {
+    let line = json!({
+      "message": "Timeout while waiting for node 12",
+      "threadId": 664
+    });
+    assert!(Filter::from(r"message~^Timeout.*node \d+$").passes(&line));
+    assert!(!Filter::from(r"message~^node").passes(&line));
+    assert!(Filter::from(r"threadId~^6\d\d$").passes(&line));
+    assert!(Filter::from(r"message!~^node").passes(&line));
+    assert!(!Filter::from(r"message!~Timeout").passes(&line));
+    assert!(!Filter::from(r"missing~.*").passes(&line));
+  }
+
   #[test]
   fn filter_equal_passes() {
     assert!(Filter::equals("app", "drive").passes(&build_line()));
@@ -148,7 +642,80 @@ mod tests {
     assert!(!Filter::not_contains("app", "riv").passes(&build_line()));
   }
 
+  fn typed_line() -> Value {
+    json!({
+      "threadId": 664,
+      "duration": 12.5,
+      "endOfBatch": false,
+      "parent": null,
+      "instant": { "epochSecond": 1622724607 },
+      "tags": ["kafka", "consumer"]
+    })
+  }
+
+  #[test]
+  fn filter_equal_on_json_types() {
+    let line = typed_line();
+    assert!(Filter::equals("threadId", "664").passes(&line));
+    assert!(Filter::equals("threadId", "664.0").passes(&line));
+    assert!(!Filter::equals("threadId", "66").passes(&line));
+    assert!(Filter::equals("duration", "12.5").passes(&line));
+    assert!(!Filter::equals("duration", "12").passes(&line));
+    assert!(Filter::equals("endOfBatch", "false").passes(&line));
+    assert!(!Filter::equals("endOfBatch", "true").passes(&line));
+    assert!(!Filter::equals("endOfBatch", "0").passes(&line));
+    assert!(Filter::equals("parent", "null").passes(&line));
+    assert!(!Filter::equals("parent", "").passes(&line));
+    assert!(Filter::equals("instant", r#"{"epochSecond":1622724607}"#).passes(&line));
+    assert!(Filter::equals("tags", r#"["kafka","consumer"]"#).passes(&line));
+    assert!(!Filter::equals("tags", "kafka").passes(&line));
+  }
+
+  #[test]
+  fn filter_contains_on_json_types() {
+    let line = typed_line();
+    assert!(Filter::contains("threadId", "66").passes(&line));
+    assert!(!Filter::contains("threadId", "7").passes(&line));
+    assert!(Filter::contains("duration", ".5").passes(&line));
+    assert!(Filter::contains("endOfBatch", "fal").passes(&line));
+    assert!(Filter::contains("parent", "null").passes(&line));
+    assert!(Filter::contains("instant", "1622724607").passes(&line));
+    assert!(Filter::contains("tags", "consumer").passes(&line));
+    assert!(!Filter::contains("tags", "producer").passes(&line));
+  }
+
+  #[test]
+  fn filter_not_contains_on_json_types() {
+    let line = typed_line();
+    assert!(!Filter::not_contains("threadId", "66").passes(&line));
+    assert!(Filter::not_contains("threadId", "7").passes(&line));
+    assert!(!Filter::not_contains("duration", ".5").passes(&line));
+    assert!(Filter::not_contains("endOfBatch", "true").passes(&line));
+    assert!(!Filter::not_contains("parent", "null").passes(&line));
+    assert!(Filter::not_contains("instant", "nanoOfSecond").passes(&line));
+    assert!(!Filter::not_contains("tags", "kafka").passes(&line));
+  }
+
+  #[test]
+  fn filter_on_nested_fields() {
+    let line = json!({
+      "@timestamp": "2020-01-13T12:34:01.740Z",
+      "mdc": { "requestId": "GB8218PtQA7" },
+      "metadata": { "user_id": "u-42" },
+      "tags": ["kafka", "consumer"],
+      "thrown": { "name": "akka.remote.OversizedPayloadException" }
+    });
+    assert!(Filter::from("mdc.requestId=GB8218PtQA7").passes(&line));
+    assert!(Filter::from("metadata.user_id=+42").passes(&line));
+    assert!(Filter::from("tags[1]=consumer").passes(&line));
+    assert!(Filter::from("thrown.name=+Oversized").passes(&line));
+    assert!(Filter::from("@timestamp=+2020-01-13").passes(&line));
+    assert!(!Filter::from("mdc.requestId=other").passes(&line));
+    assert!(!Filter::from("tags[2]=+kafka").passes(&line));
+  }
+
   #[test]
+  #[allow(clippy::useless_vec)]
   fn pass_all_filters() {
     assert!(passes_filters(
       &vec![
@@ -178,15 +745,15 @@ mod tests {
   #[test]
   fn parse_and_pass_filters() {
     assert!(passes_filters(
-      &parse_filters(vec!["app=+drive", "module=+Flink"]),
+      &parse_filters(vec!["app=+drive", "module=+Flink"]).unwrap(),
       &build_line(),
     ));
     assert!(!passes_filters(
-      &parse_filters(vec!["app=operate", "module=+Flink"]),
+      &parse_filters(vec!["app=operate", "module=+Flink"]).unwrap(),
       &build_line(),
     ));
     assert!(!passes_filters(
-      &parse_filters(vec!["app=drive", "module=^Flink"]),
+      &parse_filters(vec!["app=drive", "module=^Flink"]).unwrap(),
       &build_line(),
     ));
   }
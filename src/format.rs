@@ -1,18 +1,64 @@
+use crate::level::*;
 use crate::line_formats::*;
+use crate::search::Search;
+use crate::source::LogRecord;
+use crate::time::parse_timestamp;
+use chrono::prelude::*;
 use colored::*;
 use serde_json::Value;
 
 type LogLineToColoredString = fn(&Value) -> Option<ColoredString>;
+type LogLineToTimestamp = fn(&Value) -> Option<DateTime<Utc>>;
 
-static SPECIFIC_LINE_CONVERTERS: [LogLineToColoredString; 4] = [
-  ElixirLogLine::to_colored_string,
-  ElixirExtendedLogLine::to_colored_string,
-  LogstashJavaLogLine::to_colored_string,
-  Log4JJsonLayoutLogLine::to_colored_string,
+// How to show the lines of a format, and where they keep their time.
+struct LineFormat {
+  to_colored_string: LogLineToColoredString,
+  to_timestamp: LogLineToTimestamp,
+}
+
+impl LineFormat {
+  const fn of<T: LogLine>() -> Self {
+    LineFormat {
+      to_colored_string: T::to_colored_string,
+      to_timestamp: T::to_timestamp,
+    }
+  }
+}
+
+static SPECIFIC_LINE_FORMATS: [LineFormat; 16] = [
+  LineFormat::of::<ElixirLogLine>(),
+  LineFormat::of::<ElixirExtendedLogLine>(),
+  LineFormat::of::<LogstashJavaLogLine>(),
+  LineFormat::of::<Log4JJsonLayoutLogLine>(),
+  LineFormat::of::<BunyanLogLine>(),
+  LineFormat::of::<PinoLogLine>(),
+  LineFormat::of::<GcpLogEntry>(),
+  LineFormat::of::<GcpStructuredLogLine>(),
+  LineFormat::of::<EcsLogLine>(),
+  LineFormat::of::<ZapLogLine>(),
+  LineFormat::of::<ZerologLogLine>(),
+  LineFormat::of::<SlogLogLine>(),
+  LineFormat::of::<LogrusLogLine>(),
+  LineFormat::of::<PythonJsonLogLine>(),
+  LineFormat::of::<StructlogLogLine>(),
+  LineFormat::of::<ClefLogLine>(),
+];
+
+// Used when the structure of the line is not recognized.
+static GENERIC_TIMESTAMP_KEYS: [&str; 4] = ["@timestamp", "timestamp", "time", "ts"];
+
+static SOURCE_COLORS: [Color; 5] = [
+  Color::Cyan,
+  Color::Magenta,
+  Color::Green,
+  Color::Yellow,
+  Color::Blue,
 ];
 
 pub struct Formatter {
   last_line_converter: Option<LogLineToColoredString>,
+  // Its matches are highlighted in the formatted lines.
+  highlight: Option<Search>,
 }
 
 impl Default for Formatter {
@@ -23,17 +69,56 @@ impl Default for Formatter {
 
 impl Formatter {
   pub fn new() -> Self {
+    Self::with_highlight(None)
+  }
+
+  /// Highlights the matches of the search, which only makes sense when the
+  /// output is colored.
+  pub fn with_highlight(highlight: Option<Search>) -> Self {
     Formatter {
       last_line_converter: None,
+      highlight,
     }
   }
 
   pub fn format_not_json(&mut self, line: &str) -> String {
-    format!("{} {}", "[NOT JSON]".red(), line)
+    // Only the line is searched, not the label.
+    format!(
+      "{} {}",
+      "[NOT JSON]".red(),
+      self.highlighted(line.to_string())
+    )
   }
 
   pub fn format_message(&mut self, entry: Value) -> String {
-    format!("{}", self.color_format_message(entry))
+    let text = format!("{}", self.color_format_message(entry));
+    self.highlighted(text)
+  }
+
+  /// Formats a line as it was read, JSON or not.
+  pub fn format_record(&mut self, record: LogRecord) -> String {
+    let text = match (record.json, record.prefix) {
+      (None, _) => self.format_not_json(&record.line),
+      // The text found before the JSON on the line comes first, whatever the
+      // format.
+      (Some(entry), Some(prefix)) => {
+        let text = format!("{} {}", prefix.dimmed(), self.color_format_message(entry));
+        self.highlighted(text)
+      }
+      (Some(entry), None) => self.format_message(entry),
+    };
+    // The stream a container wrote the line to, whatever the line.
+    match record.container {
+      Some(container) => format!("{} {}", format!("[{}]", container.stream).dimmed(), text),
+      None => text,
+    }
+  }
+
+  fn highlighted(&self, text: String) -> String {
+    match &self.highlight {
+      Some(search) => search.highlight(&text),
+      None => text,
+    }
   }
 
   fn color_format_message(&mut self, entry: Value) -> ColoredString {
@@ -51,13 +136,16 @@ impl Formatter {
       println!("color_format_message_try_each");
     }
 
-    for convert in SPECIFIC_LINE_CONVERTERS.iter() {
+    for convert in SPECIFIC_LINE_FORMATS
+      .iter()
+      .map(|format| format.to_colored_string)
+    {
       match convert(&entry) {
         Some(colored_string) => {
           if cfg!(test) {
             println!("Convert picked");
           }
-          self.last_line_converter = Some(*convert);
+          self.last_line_converter = Some(convert);
           return colored_string;
         }
         _ => continue,
@@ -85,6 +173,22 @@ impl Formatter {
   }
 }
 
+pub fn timestamp_of(entry: &Value) -> Option<DateTime<Utc>> {
+  SPECIFIC_LINE_FORMATS
+    .iter()
+    .find_map(|format| (format.to_timestamp)(entry))
+    .or_else(|| {
+      GENERIC_TIMESTAMP_KEYS
+        .iter()
+        .filter_map(|key| entry.get(key).and_then(Value::as_str))
+        .find_map(parse_timestamp)
+    })
+}
+
+pub fn format_source(name: &str, width: usize, index: usize) -> ColoredString {
+  format!("{name:<width$} |").color(SOURCE_COLORS[index % SOURCE_COLORS.len()])
+}
+
 fn format_generic_json(entry: &Value) -> ColoredString {
   match entry {
     Value::Object(map) => {
@@ -101,8 +205,11 @@ fn format_generic_json(entry: &Value) -> ColoredString {
       } else {
         "".to_string()
       };
-      let level = entry.get("level").unwrap_or(&Value::Null).as_str();
-      colored_with_maybe_level(level, &format!("{}{}", &meta.trim().dimmed(), &text))
+      let text = format!("{}{}", &meta.trim().dimmed(), &text);
+      match level_of(entry) {
+        None => text.normal(),
+        level => colored_with_severity(level, &text),
+      }
     }
     _ => panic!("Unsupported parsed json"),
   }
@@ -116,33 +223,27 @@ fn text_value(val: &Value) -> String {
   }
 }
 
-fn colored_with_maybe_level(maybe_level: Option<&str>, text: &str) -> ColoredString {
-  match maybe_level {
-    None => text.normal(),
-    Some(level) => colored_with_level(level, text),
-  }
+pub fn colored_with_level(level: &str, text: &str) -> ColoredString {
+  colored_with_severity(level.parse().ok(), text)
 }
 
-pub fn colored_with_level(level: &str, text: &str) -> ColoredString {
-  match level.to_lowercase().as_str() {
-    "emergency" => text.white().on_bright_red(),
-    "alert" => text.white().on_bright_red(),
-    "fatal" => text.white().on_bright_red(),
-    "critical" => text.red(),
-    "error" => text.red(),
-    "warn" => text.yellow(),
-    "warning" => text.yellow(),
-    "notice" => text.cyan(),
-    "info" => text.white(),
-    "debug" => text.blue(),
-    "trace" => text.purple(),
-    _ => text.white(),
+fn colored_with_severity(level: Option<Level>, text: &str) -> ColoredString {
+  match level {
+    Some(Level::Emergency | Level::Alert | Level::Fatal) => text.white().on_bright_red(),
+    Some(Level::Critical | Level::Error) => text.red(),
+    Some(Level::Warning) => text.yellow(),
+    Some(Level::Notice) => text.cyan(),
+    Some(Level::Info) => text.white(),
+    Some(Level::Debug) => text.blue(),
+    Some(Level::Trace) => text.purple(),
+    None => text.white(),
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::container::ContainerMeta;
 
   fn join(texts: Vec<ColoredString>) -> String {
     texts
@@ -168,6 +269,19 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_highlight_not_json_line_without_label() {
+    let search: Search = "NOT".parse().unwrap();
+    assert_eq!(
+      Formatter::with_highlight(Some(search.clone())).format_not_json("NOT found"),
+      format!("{} {}", "[NOT JSON]".red(), search.highlight("NOT found"))
+    );
+    assert_eq!(
+      Formatter::with_highlight(Some("JSON".parse().unwrap())).format_not_json("NOT found"),
+      Formatter::new().format_not_json("NOT found")
+    );
+  }
+
   #[test]
   fn test_format_minimal_working_line() {
     println!(
@@ -380,6 +494,522 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_format_mdc_values_as_json() {
+    let mut line = logstash_java_line();
+    line["mdc"] = json!({"userId": 42, "tags": ["a", "b"], "requestId": "r-1"});
+    assert_eq!(
+      Formatter::new().format_message(line),
+      render(join(vec![
+        "[2020-01-13T12:34:01.740Z] [DEBUG] [org.apache.flink.runtime.dispatcher.StandaloneDispatcher] [flink-akka.actor.default-dispatcher-3][requestId=r-1,tags=[\"a\",\"b\"],userId=42]".dimmed(),
+        " Dispatcher akka.tcp://flink@04fc4fd30dc3:6123/user/dispatcher accepted leadership with fencing token 00000000000000000000000000000000. Start recovered jobs.".normal()
+      ]).blue())
+    );
+
+    let mut line = log4j_json_layout_java_line();
+    line["mdc"] = json!({"retries": 3, "dryRun": false});
+    assert_eq!(
+      Formatter::new().format_message(line),
+      render(join(vec![
+        "[2021-06-03T12:50:07.420+00:00] [WARN] [org.apache.flink.runtime.dispatcher.DispatcherRestEndpoint] [main][dryRun=false,retries=3]".dimmed(),
+        " Starting rest endpoint.".normal()
+      ]).yellow())
+    );
+  }
+
+  #[test]
+  fn test_format_line_with_prefix() {
+    let record = LogRecord::parse(0, format!("host app[123]: {}", elixir_line()));
+    assert_eq!(
+      Formatter::new().format_record(record),
+      format!(
+        "{} {}",
+        "host app[123]:".dimmed(),
+        Formatter::new().format_message(elixir_line())
+      )
+    );
+  }
+
+  #[test]
+  fn test_format_container_lines() {
+    let in_container = |line: &str| LogRecord {
+      container: Some(ContainerMeta {
+        stream: "stderr".to_string(),
+        time: "2024-01-01T00:00:00Z".to_string(),
+      }),
+      ..LogRecord::parse(0, line.to_string())
+    };
+    let stream = "[stderr]".dimmed();
+    assert_eq!(
+      Formatter::new().format_record(in_container(&random_line().to_string())),
+      format!("{} {}", stream, render(format_generic_json(&random_line())))
+    );
+    assert_eq!(
+      Formatter::new().format_record(in_container("Listening on 8080")),
+      format!(
+        "{} {}",
+        stream,
+        Formatter::new().format_not_json("Listening on 8080")
+      )
+    );
+  }
+
+  #[test]
+  fn test_format_bunyan_line() {
+    assert_eq!(
+      Formatter::new().format_message(bunyan_line()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.123Z] [error] [api] [web-1/4242][req_id=r-1]".dimmed(),
+          " request failed".normal(),
+          "\n\tError: boom\n\t    at handler (/app/index.js:10:5)".red(),
+        ])
+        .red()
+      )
+    );
+  }
+
+  fn bunyan_line() -> Value {
+    json!({
+      "name": "api",
+      "hostname": "web-1",
+      "pid": 4242,
+      "level": 50,
+      "req_id": "r-1",
+      "msg": "request failed",
+      "time": "2024-05-01T10:52:00.123Z",
+      "err": {
+        "message": "boom",
+        "name": "Error",
+        "stack": "Error: boom\n    at handler (/app/index.js:10:5)"
+      },
+      "v": 0
+    })
+  }
+
+  #[test]
+  fn test_format_pino_line() {
+    assert_eq!(
+      Formatter::new().format_message(pino_line()),
+      render(
+        join(vec![
+          "[2018-07-09T21:17:54.631Z] [warn] [web-1/657]".dimmed(),
+          " slow query".normal(),
+          "\n\tTimeoutError: took 3s".red(),
+        ])
+        .yellow()
+      )
+    );
+  }
+
+  #[test]
+  fn test_lines_with_a_level_and_a_time_are_not_pino() {
+    let line = json!({"level": 30, "time": 1531171074631i64, "message": "started"});
+    assert!(PinoLogLine::to_colored_string(&line).is_none());
+    assert_eq!(
+      Formatter::new().format_message(line.clone()),
+      render(format_generic_json(&line))
+    );
+  }
+
+  fn pino_line() -> Value {
+    json!({
+      "level": 40,
+      "time": 1531171074631i64,
+      "pid": 657,
+      "hostname": "web-1",
+      "msg": "slow query",
+      "err": { "type": "TimeoutError", "message": "took 3s" }
+    })
+  }
+
+  #[test]
+  fn test_format_gcp_structured_line() {
+    assert_eq!(
+      Formatter::new().format_message(gcp_structured_line()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.123456Z] [ERROR] [4bf92f3577b34da6/00f067aa0ba902b7][env=prod][order=42]"
+            .dimmed(),
+          " payment failed".normal(),
+          "\n\tjava.lang.IllegalStateException: declined\n\t\tat Pay.run(Pay.java:12)".red(),
+        ])
+        .red()
+      )
+    );
+  }
+
+  fn gcp_structured_line() -> Value {
+    json!({
+      "severity": "ERROR",
+      "message": "payment failed",
+      "time": "2024-05-01T10:52:00.123456Z",
+      "logging.googleapis.com/trace": "projects/shop/traces/4bf92f3577b34da6",
+      "logging.googleapis.com/spanId": "00f067aa0ba902b7",
+      "logging.googleapis.com/labels": { "env": "prod" },
+      "logging.googleapis.com/sourceLocation": { "file": "Pay.java", "line": "12" },
+      "stack_trace": "java.lang.IllegalStateException: declined\n\tat Pay.run(Pay.java:12)",
+      "order": 42
+    })
+  }
+
+  #[test]
+  fn test_format_gcp_log_entry() {
+    assert_eq!(
+      Formatter::new().format_message(gcp_log_entry()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:01.5Z] [WARNING] [k8s_container] [stdout] [4bf92f3577b34da6][pod=web-1][latency_ms=812]"
+            .dimmed(),
+          " slow response".normal(),
+        ])
+        .yellow()
+      )
+    );
+    assert_eq!(
+      Formatter::new().format_message(json!({
+        "logName": "projects/shop/logs/cloudaudit.googleapis.com%2Factivity",
+        "timestamp": "2024-05-01T10:52:02Z",
+        "protoPayload": { "methodName": "storage.buckets.create" }
+      })),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:02Z] [DEFAULT] [cloudaudit.googleapis.com/activity]".dimmed(),
+          " storage.buckets.create".normal(),
+        ])
+        .white()
+      )
+    );
+  }
+
+  fn gcp_log_entry() -> Value {
+    json!({
+      "insertId": "abc123",
+      "jsonPayload": { "message": "slow response", "latency_ms": 812 },
+      "labels": { "pod": "web-1" },
+      "logName": "projects/shop/logs/stdout",
+      "receiveTimestamp": "2024-05-01T10:52:02.1Z",
+      "resource": { "type": "k8s_container", "labels": { "cluster_name": "main" } },
+      "severity": "WARNING",
+      "timestamp": "2024-05-01T10:52:01.5Z",
+      "trace": "projects/shop/traces/4bf92f3577b34da6"
+    })
+  }
+
+  #[test]
+  fn test_format_ecs_line() {
+    let expected = render(
+      join(vec![
+        "[2024-05-01T10:52:00.123Z] [ERROR] [billing] [com.shop.Billing] [main][trace.id=4bf92f35]"
+          .dimmed(),
+        " charge failed".normal(),
+        "\n\tjava.lang.IllegalStateException: declined\n\t\tat Billing.charge(Billing.java:12)"
+          .red(),
+      ])
+      .red(),
+    );
+    assert_eq!(Formatter::new().format_message(ecs_line()), expected);
+    // The same fields, as nested objects.
+    assert_eq!(
+      Formatter::new().format_message(json!({
+        "@timestamp": "2024-05-01T10:52:00.123Z",
+        "log": { "level": "ERROR", "logger": "com.shop.Billing" },
+        "message": "charge failed",
+        "ecs": { "version": "1.2.0" },
+        "service": { "name": "billing" },
+        "process": { "thread": { "name": "main" } },
+        "trace": { "id": "4bf92f35" },
+        "error": {
+          "type": "java.lang.IllegalStateException",
+          "stack_trace": "java.lang.IllegalStateException: declined\n\tat Billing.charge(Billing.java:12)"
+        }
+      })),
+      expected
+    );
+    assert_eq!(
+      Formatter::new().format_message(json!({
+        "@timestamp": "2024-05-01T10:52:00.123Z",
+        "log.level": "warn",
+        "message": "retrying",
+        "error.message": "timed out",
+        "error.type": "TimeoutError"
+      })),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.123Z] [warn]".dimmed(),
+          " retrying".normal(),
+          "\n\ttimed out (TimeoutError)".red(),
+        ])
+        .yellow()
+      )
+    );
+  }
+
+  fn ecs_line() -> Value {
+    json!({
+      "@timestamp": "2024-05-01T10:52:00.123Z",
+      "log.level": "ERROR",
+      "message": "charge failed",
+      "ecs.version": "1.2.0",
+      "service.name": "billing",
+      "log.logger": "com.shop.Billing",
+      "process.thread.name": "main",
+      "trace.id": "4bf92f35",
+      "error.type": "java.lang.IllegalStateException",
+      "error.stack_trace": "java.lang.IllegalStateException: declined\n\tat Billing.charge(Billing.java:12)"
+    })
+  }
+
+  #[test]
+  fn test_format_zap_line() {
+    assert_eq!(
+      Formatter::new().format_message(zap_line()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.123456Z] [error] [api] [server/handler.go:42][status=502]".dimmed(),
+          " upstream failed".normal(),
+          "\n\tmain.handle\n\t\t/app/server/handler.go:42".red(),
+        ])
+        .red()
+      )
+    );
+  }
+
+  fn zap_line() -> Value {
+    json!({
+      "level": "error",
+      "ts": 1714560720.123456,
+      "logger": "api",
+      "caller": "server/handler.go:42",
+      "msg": "upstream failed",
+      "status": 502,
+      "stacktrace": "main.handle\n\t/app/server/handler.go:42"
+    })
+  }
+
+  #[test]
+  fn test_format_zerolog_line() {
+    assert_eq!(
+      Formatter::new().format_message(zerolog_line()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00Z] [warn] [/app/main.go:18][attempt=2]".dimmed(),
+          " retrying".normal(),
+          "\n\tconnection refused".red(),
+        ])
+        .yellow()
+      )
+    );
+    // The time as milliseconds since the epoch.
+    assert_eq!(
+      Formatter::new().format_message(json!({
+        "level": "info",
+        "time": 1714560720500i64,
+        "message": "started"
+      })),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.500Z] [info]".dimmed(),
+          " started".normal()
+        ])
+        .white()
+      )
+    );
+  }
+
+  fn zerolog_line() -> Value {
+    json!({
+      "level": "warn",
+      "time": "2024-05-01T10:52:00Z",
+      "caller": "/app/main.go:18",
+      "message": "retrying",
+      "error": "connection refused",
+      "attempt": 2
+    })
+  }
+
+  #[test]
+  fn test_format_logrus_line() {
+    assert_eq!(
+      Formatter::new().format_message(logrus_line()),
+      render(
+        join(vec![
+          "[2024-05-01T12:52:00+02:00] [warning][user=ana]".dimmed(),
+          " quota almost reached".normal(),
+        ])
+        .yellow()
+      )
+    );
+  }
+
+  fn logrus_line() -> Value {
+    json!({
+      "level": "warning",
+      "msg": "quota almost reached",
+      "time": "2024-05-01T12:52:00+02:00",
+      "user": "ana"
+    })
+  }
+
+  #[test]
+  fn test_format_slog_line() {
+    assert_eq!(
+      Formatter::new().format_message(slog_line()),
+      render(
+        join(vec![
+          "[2024-05-01T12:52:00.123456789+02:00] [ERROR+2] [/app/main.go:31][err=disk full]"
+            .dimmed(),
+          " write failed".normal(),
+        ])
+        .red()
+      )
+    );
+  }
+
+  fn slog_line() -> Value {
+    json!({
+      "time": "2024-05-01T12:52:00.123456789+02:00",
+      "level": "ERROR+2",
+      "source": { "function": "main.main", "file": "/app/main.go", "line": 31 },
+      "msg": "write failed",
+      "err": "disk full"
+    })
+  }
+
+  #[test]
+  fn test_format_python_json_line() {
+    assert_eq!(
+      Formatter::new().format_message(python_json_line()),
+      render(
+        join(vec![
+          "[2024-05-01 10:52:00,123] [CRITICAL] [app.jobs][job=sync]".dimmed(),
+          " job crashed".normal(),
+          "\n\tTraceback (most recent call last):\n\t  File \"jobs.py\", line 8, in run\n\tKeyError: 'id'"
+            .red(),
+        ])
+        .red()
+      )
+    );
+  }
+
+  fn python_json_line() -> Value {
+    json!({
+      "asctime": "2024-05-01 10:52:00,123",
+      "levelname": "CRITICAL",
+      "name": "app.jobs",
+      "message": "job crashed",
+      "exc_info": "Traceback (most recent call last):\n  File \"jobs.py\", line 8, in run\nKeyError: 'id'",
+      "job": "sync"
+    })
+  }
+
+  #[test]
+  fn test_format_structlog_line() {
+    assert_eq!(
+      Formatter::new().format_message(structlog_line()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.250Z] [warning] [app.api][user_id=7]".dimmed(),
+          " login failed".normal(),
+        ])
+        .yellow()
+      )
+    );
+  }
+
+  fn structlog_line() -> Value {
+    json!({
+      "event": "login failed",
+      "level": "warning",
+      "logger": "app.api",
+      "timestamp": 1714560720.25,
+      "user_id": 7
+    })
+  }
+
+  #[test]
+  fn test_format_clef_line() {
+    assert_eq!(
+      Formatter::new().format_message(clef_line()),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:00.1234567Z] [Information] [Shop.Orders][RequestId=r-1]".dimmed(),
+          " Order 42 placed by ana".normal(),
+        ])
+        .white()
+      )
+    );
+    assert_eq!(
+      Formatter::new().format_message(json!({
+        "@t": "2024-05-01T10:52:01Z",
+        "@m": "Payment failed",
+        "@i": "a1b2c3d4",
+        "@l": "Error",
+        "@x": "System.InvalidOperationException: declined\r\n   at Shop.Pay()"
+      })),
+      render(
+        join(vec![
+          "[2024-05-01T10:52:01Z] [Error]".dimmed(),
+          " Payment failed".normal(),
+          "\n\tSystem.InvalidOperationException: declined\r\n\t   at Shop.Pay()".red(),
+        ])
+        .red()
+      )
+    );
+  }
+
+  fn clef_line() -> Value {
+    json!({
+      "@t": "2024-05-01T10:52:00.1234567Z",
+      "@mt": "Order {OrderId} placed by {User}",
+      "OrderId": 42,
+      "User": "ana",
+      "RequestId": "r-1",
+      "SourceContext": "Shop.Orders"
+    })
+  }
+
+  #[test]
+  fn test_timestamp_of_recognized_lines() {
+    let utc = |text| {
+      Some(
+        DateTime::parse_from_rfc3339(text)
+          .unwrap()
+          .with_timezone(&Utc),
+      )
+    };
+    assert_eq!(
+      timestamp_of(&elixir_line()),
+      utc("2019-12-18T10:55:50.000345Z")
+    );
+    assert_eq!(
+      timestamp_of(&logstash_java_line()),
+      utc("2020-01-13T12:34:01.740Z")
+    );
+    assert_eq!(
+      timestamp_of(&log4j_json_layout_java_line()),
+      utc("2021-06-03T12:50:07.420Z")
+    );
+    assert_eq!(
+      timestamp_of(&json!({"time": "2024-01-01T00:00:00Z", "msg": "hi"})),
+      utc("2024-01-01T00:00:00Z")
+    );
+    assert_eq!(
+      timestamp_of(&bunyan_line()),
+      utc("2024-05-01T10:52:00.123Z")
+    );
+    assert_eq!(timestamp_of(&pino_line()), utc("2018-07-09T21:17:54.631Z"));
+    assert_eq!(
+      timestamp_of(&gcp_structured_line()),
+      utc("2024-05-01T10:52:00.123456Z")
+    );
+    assert_eq!(
+      timestamp_of(&gcp_log_entry()),
+      utc("2024-05-01T10:52:01.5Z")
+    );
+    assert_eq!(timestamp_of(&random_line()), None);
+  }
+
   fn log4j_json_layout_java_line() -> Value {
     json!({
       "thread": "main",
@@ -0,0 +1,477 @@
+use bzip2::bufread::MultiBzDecoder;
+use flate2::bufread::MultiGzDecoder;
+use glob::glob;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use xz2::bufread::XzDecoder;
+
+pub const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+// Followed by the block size, from `1` to `9`.
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+const LONGEST_MAGIC: usize = 6;
+
+pub type Lines = Box<dyn Iterator<Item = io::Result<String>>>;
+
+/// Expands the glob patterns among the given paths. Paths without glob
+/// characters are kept as they are, so that a missing file is reported when
+/// opening it.
+pub fn expand_input_paths(patterns: &[String]) -> io::Result<Vec<String>> {
+  let mut paths = Vec::new();
+  for pattern in patterns {
+    if !pattern.contains(['*', '?', '[']) {
+      paths.push(pattern.clone());
+      continue;
+    }
+    let entries = glob(pattern).map_err(|e| invalid_input(format!("{pattern}: {e}")))?;
+    let matched = entries
+      .filter_map(Result::ok)
+      .map(|path| path.to_string_lossy().into_owned())
+      .collect::<Vec<String>>();
+    if matched.is_empty() {
+      return Err(invalid_input(format!("{pattern}: no matching file")));
+    }
+    paths.extend(matched);
+  }
+  Ok(paths)
+}
+
+fn invalid_input(message: String) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+pub fn open_lines(maybe_file_path: Option<&String>) -> io::Result<Lines> {
+  let reader: Box<dyn BufRead> = match maybe_file_path {
+    Some(file_path) => {
+      let file = File::open(file_path).map_err(|e| with_path(file_path, e))?;
+      Box::new(BufReader::new(file))
+    }
+    None => Box::new(io::stdin().lock()),
+  };
+  let reader = decompressed(reader).map_err(|e| match maybe_file_path {
+    Some(file_path) => with_path(file_path, e),
+    None => e,
+  })?;
+  Ok(Box::new(reader.lines()))
+}
+
+/// Wraps the reader in a streaming decoder if its content starts with the
+/// magic bytes of a supported compression format (gzip, zstd, bzip2, xz).
+pub fn decompressed<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+  let header = read_header(&mut reader)?;
+  let compression = Compression::detect(&header);
+  let reader = Cursor::new(header).chain(reader);
+
+  Ok(match compression {
+    None => Box::new(reader),
+    Some(Compression::Gzip) => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+    // Read as plain text when the decoder can't be set up.
+    Some(Compression::Zstd) => match zstd::stream::raw::Decoder::new() {
+      Err(_) => Box::new(reader),
+      Ok(decoder) => Box::new(BufReader::new(zstd::stream::zio::Reader::new(
+        reader, decoder,
+      ))),
+    },
+    Some(Compression::Bzip2) => Box::new(BufReader::new(MultiBzDecoder::new(reader))),
+    Some(Compression::Xz) => Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader))),
+  })
+}
+
+// Takes what the reader has available, and only waits for more while it could
+// still be the start of a magic number: pipes can give fewer bytes than needed
+// on the first read, but a short first line isn't held back.
+fn read_header<R: BufRead>(reader: &mut R) -> io::Result<Vec<u8>> {
+  let mut header = Vec::new();
+  while Compression::detect(&header).is_none() && Compression::may_start(&header) {
+    let available = reader.fill_buf()?;
+    if available.is_empty() {
+      break;
+    }
+    let taken = available.len().min(LONGEST_MAGIC - header.len());
+    header.extend_from_slice(&available[..taken]);
+    reader.consume(taken);
+  }
+  Ok(header)
+}
+
+#[derive(Clone, Copy)]
+enum Compression {
+  Gzip,
+  Zstd,
+  Bzip2,
+  Xz,
+}
+
+impl Compression {
+  const ALL: [Compression; 4] = [
+    Compression::Gzip,
+    Compression::Zstd,
+    Compression::Bzip2,
+    Compression::Xz,
+  ];
+
+  fn detect(header: &[u8]) -> Option<Self> {
+    Compression::ALL
+      .iter()
+      .copied()
+      .find(|compression| compression.header_len() <= header.len() && compression.accepts(header))
+  }
+
+  // Whether more bytes could make the header one of a supported format.
+  fn may_start(header: &[u8]) -> bool {
+    Compression::ALL
+      .iter()
+      .any(|compression| header.len() < compression.header_len() && compression.accepts(header))
+  }
+
+  fn header_len(self) -> usize {
+    match self {
+      Compression::Gzip => GZIP_MAGIC.len(),
+      Compression::Zstd => ZSTD_MAGIC.len(),
+      Compression::Bzip2 => BZIP2_MAGIC.len() + 1,
+      Compression::Xz => XZ_MAGIC.len(),
+    }
+  }
+
+  // Whether the header, or what there is of it, matches this format's.
+  fn accepts(self, header: &[u8]) -> bool {
+    let magic = match self {
+      Compression::Gzip => GZIP_MAGIC,
+      Compression::Zstd => ZSTD_MAGIC,
+      Compression::Bzip2 => BZIP2_MAGIC,
+      Compression::Xz => XZ_MAGIC,
+    };
+    let header = &header[..header.len().min(self.header_len())];
+    header
+      .iter()
+      .enumerate()
+      .all(|(index, byte)| match magic.get(index) {
+        Some(expected) => byte == expected,
+        // The block size of bzip2.
+        None => (b'1'..=b'9').contains(byte),
+      })
+  }
+}
+
+pub fn open_follow_reader(file_path: &String) -> io::Result<FollowReader> {
+  FollowReader::open(file_path).map_err(|e| with_path(file_path, e))
+}
+
+fn with_path(path: &str, error: io::Error) -> io::Error {
+  io::Error::new(error.kind(), format!("{path}: {error}"))
+}
+
+/// Reads a file line by line like `tail -F`: at the end of the file it waits
+/// for more data instead of stopping, and reopens the file when it gets
+/// truncated or replaced (rename + recreate).
+pub struct FollowReader {
+  path: PathBuf,
+  reader: BufReader<File>,
+  identity: Option<FileIdentity>,
+  position: u64,
+  partial: String,
+}
+
+impl FollowReader {
+  pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+    let path = path.as_ref().to_path_buf();
+    let file = File::open(&path)?;
+    let identity = file_identity(&file.metadata()?);
+    Ok(FollowReader {
+      path,
+      reader: BufReader::new(file),
+      identity,
+      position: 0,
+      partial: String::new(),
+    })
+  }
+
+  /// Returns the next complete line, or `None` if no complete line has been
+  /// written yet. Never blocks.
+  pub fn poll_line(&mut self) -> io::Result<Option<String>> {
+    loop {
+      let read = self.reader.read_line(&mut self.partial)?;
+      if read == 0 {
+        if self.reopen_if_rotated()? {
+          continue;
+        }
+        return Ok(None);
+      }
+      self.position += read as u64;
+      if self.partial.ends_with('\n') {
+        let mut line = std::mem::take(&mut self.partial);
+        line.pop();
+        if line.ends_with('\r') {
+          line.pop();
+        }
+        return Ok(Some(line));
+      }
+    }
+  }
+
+  fn reopen_if_rotated(&mut self) -> io::Result<bool> {
+    let metadata = match fs::metadata(&self.path) {
+      // Renamed away and not recreated yet: keep waiting.
+      Err(_) => return Ok(false),
+      Ok(metadata) => metadata,
+    };
+
+    if file_identity(&metadata) != self.identity {
+      let file = File::open(&self.path)?;
+      self.identity = file_identity(&file.metadata()?);
+      self.reader = BufReader::new(file);
+    } else if metadata.len() < self.position {
+      self.reader.seek(SeekFrom::Start(0))?;
+    } else {
+      return Ok(false);
+    }
+
+    self.position = 0;
+    self.partial.clear();
+    Ok(true)
+  }
+}
+
+impl Iterator for FollowReader {
+  type Item = io::Result<String>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      match self.poll_line() {
+        Ok(Some(line)) => return Some(Ok(line)),
+        Ok(None) => thread::sleep(FOLLOW_POLL_INTERVAL),
+        Err(e) => return Some(Err(e)),
+      }
+    }
+  }
+}
+
+#[derive(PartialEq, Debug)]
+struct FileIdentity {
+  device: u64,
+  inode: u64,
+}
+
+#[cfg(unix)]
+fn file_identity(metadata: &fs::Metadata) -> Option<FileIdentity> {
+  use std::os::unix::fs::MetadataExt;
+  Some(FileIdentity {
+    device: metadata.dev(),
+    inode: metadata.ino(),
+  })
+}
+
+// Without inodes, only truncation can be detected.
+#[cfg(not(unix))]
+fn file_identity(_metadata: &fs::Metadata) -> Option<FileIdentity> {
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+
+  fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("jaslog-{}-{}", std::process::id(), name))
+  }
+
+  fn append(path: &Path, text: &str) {
+    let mut file = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(path)
+      .unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+  }
+
+  const LINES: &str = "{\"level\":\"info\",\"message\":\"one\"}\nnot json\n";
+
+  fn decompressed_lines(bytes: Vec<u8>) -> Vec<String> {
+    decompressed(Cursor::new(bytes))
+      .unwrap()
+      .lines()
+      .map(|line| line.unwrap())
+      .collect()
+  }
+
+  fn expected_lines() -> Vec<String> {
+    LINES.lines().map(String::from).collect()
+  }
+
+  #[test]
+  fn plain_text_is_left_untouched() {
+    assert_eq!(
+      decompressed_lines(LINES.as_bytes().to_vec()),
+      expected_lines()
+    );
+    assert_eq!(decompressed_lines(b"{}".to_vec()), vec!["{}".to_string()]);
+    assert!(decompressed_lines(vec![]).is_empty());
+  }
+
+  #[test]
+  fn text_starting_like_a_magic_number_is_plain() {
+    assert_eq!(
+      decompressed_lines(b"BZh is a word\n".to_vec()),
+      vec!["BZh is a word".to_string()]
+    );
+    assert_eq!(decompressed_lines(b"BZh".to_vec()), vec!["BZh".to_string()]);
+  }
+
+  // Gives its chunks one read at a time, like a pipe, and then fails as if
+  // nothing more was written yet.
+  struct Chunks(Vec<Vec<u8>>);
+
+  impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+      if self.0.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::WouldBlock, "nothing yet"));
+      }
+      let chunk = self.0.remove(0);
+      buf[..chunk.len()].copy_from_slice(&chunk);
+      Ok(chunk.len())
+    }
+  }
+
+  #[test]
+  fn short_first_line_is_not_held_back() {
+    let reader = decompressed(BufReader::new(Chunks(vec![b"{}\n".to_vec()]))).unwrap();
+    assert_eq!(reader.lines().next().unwrap().unwrap(), "{}");
+  }
+
+  #[test]
+  fn header_split_across_reads_is_detected() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(LINES.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let chunks = vec![compressed[..1].to_vec(), compressed[1..].to_vec()];
+    let lines = decompressed(BufReader::new(Chunks(chunks)))
+      .unwrap()
+      .lines()
+      .take(2)
+      .map(|line| line.unwrap())
+      .collect::<Vec<String>>();
+    assert_eq!(lines, expected_lines());
+  }
+
+  #[test]
+  fn gzip_is_decompressed() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(LINES.as_bytes()).unwrap();
+    assert_eq!(
+      decompressed_lines(encoder.finish().unwrap()),
+      expected_lines()
+    );
+  }
+
+  #[test]
+  fn zstd_is_decompressed() {
+    let compressed = zstd::encode_all(LINES.as_bytes(), 0).unwrap();
+    assert_eq!(decompressed_lines(compressed), expected_lines());
+  }
+
+  #[test]
+  fn bzip2_is_decompressed() {
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(LINES.as_bytes()).unwrap();
+    assert_eq!(
+      decompressed_lines(encoder.finish().unwrap()),
+      expected_lines()
+    );
+  }
+
+  #[test]
+  fn xz_is_decompressed() {
+    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(LINES.as_bytes()).unwrap();
+    assert_eq!(
+      decompressed_lines(encoder.finish().unwrap()),
+      expected_lines()
+    );
+  }
+
+  #[test]
+  fn expand_globs_only() {
+    let dir = temp_path("expand");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("svc-b.log"), "").unwrap();
+    fs::write(dir.join("svc-a.log"), "").unwrap();
+    fs::write(dir.join("other.log"), "").unwrap();
+    let dir_name = dir.to_string_lossy();
+
+    assert_eq!(
+      expand_input_paths(&["plain.log".to_string(), format!("{dir_name}/svc-*.log"),]).unwrap(),
+      vec![
+        "plain.log".to_string(),
+        format!("{dir_name}/svc-a.log"),
+        format!("{dir_name}/svc-b.log"),
+      ]
+    );
+    assert!(expand_input_paths(&[format!("{dir_name}/nothing-*.log")]).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn follow_picks_up_appended_lines() {
+    let path = temp_path("follow-append.log");
+    fs::write(&path, "first\n").unwrap();
+    let mut reader = FollowReader::open(&path).unwrap();
+
+    assert_eq!(reader.poll_line().unwrap(), Some("first".to_string()));
+    assert_eq!(reader.poll_line().unwrap(), None);
+
+    append(&path, "sec");
+    assert_eq!(reader.poll_line().unwrap(), None);
+    append(&path, "ond\r\n");
+    assert_eq!(reader.poll_line().unwrap(), Some("second".to_string()));
+
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn follow_survives_truncation() {
+    let path = temp_path("follow-truncate.log");
+    fs::write(&path, "a rather long first line\n").unwrap();
+    let mut reader = FollowReader::open(&path).unwrap();
+    assert_eq!(
+      reader.poll_line().unwrap(),
+      Some("a rather long first line".to_string())
+    );
+
+    fs::write(&path, "short\n").unwrap();
+    assert_eq!(reader.poll_line().unwrap(), Some("short".to_string()));
+
+    fs::remove_file(&path).unwrap();
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn follow_survives_rename_and_recreate() {
+    let path = temp_path("follow-rotate.log");
+    let rotated = temp_path("follow-rotate.log.1");
+    fs::write(&path, "before\n").unwrap();
+    let mut reader = FollowReader::open(&path).unwrap();
+    assert_eq!(reader.poll_line().unwrap(), Some("before".to_string()));
+
+    append(&path, "last words\n");
+    fs::rename(&path, &rotated).unwrap();
+    assert_eq!(reader.poll_line().unwrap(), Some("last words".to_string()));
+    assert_eq!(reader.poll_line().unwrap(), None);
+
+    fs::write(&path, "after a long while\n").unwrap();
+    assert_eq!(
+      reader.poll_line().unwrap(),
+      Some("after a long while".to_string())
+    );
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&rotated).unwrap();
+  }
+}
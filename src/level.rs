@@ -0,0 +1,245 @@
+use crate::path::FieldPath;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Log levels, ordered by severity.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Level {
+  Trace,
+  Debug,
+  Info,
+  Notice,
+  Warning,
+  Error,
+  Critical,
+  Fatal,
+  Alert,
+  Emergency,
+}
+
+// Fields holding the level, by order of preference.
+static LEVEL_KEYS: [&str; 5] = ["level", "severity", "levelname", "log.level", "@l"];
+
+impl Level {
+  pub fn from_name(name: &str) -> Option<Level> {
+    match name.trim().to_lowercase().as_str() {
+      "emergency" | "emerg" | "panic" => Some(Level::Emergency),
+      "alert" => Some(Level::Alert),
+      "fatal" => Some(Level::Fatal),
+      "critical" | "crit" => Some(Level::Critical),
+      "error" | "err" => Some(Level::Error),
+      "warn" | "warning" => Some(Level::Warning),
+      "notice" => Some(Level::Notice),
+      "info" | "information" | "informational" => Some(Level::Info),
+      "debug" => Some(Level::Debug),
+      "trace" | "verbose" => Some(Level::Trace),
+      _ => None,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Level::Trace => "trace",
+      Level::Debug => "debug",
+      Level::Info => "info",
+      Level::Notice => "notice",
+      Level::Warning => "warn",
+      Level::Error => "error",
+      Level::Critical => "critical",
+      Level::Fatal => "fatal",
+      Level::Alert => "alert",
+      Level::Emergency => "emergency",
+    }
+  }
+
+  /// Bunyan/Pino levels (10 to 60) or syslog severities (0 to 7).
+  pub fn from_number(number: i64) -> Option<Level> {
+    match number {
+      0 => Some(Level::Emergency),
+      1 => Some(Level::Alert),
+      2 => Some(Level::Critical),
+      3 => Some(Level::Error),
+      4 => Some(Level::Warning),
+      5 => Some(Level::Notice),
+      6 => Some(Level::Info),
+      7 => Some(Level::Debug),
+      10..=19 => Some(Level::Trace),
+      20..=29 => Some(Level::Debug),
+      30..=39 => Some(Level::Info),
+      40..=49 => Some(Level::Warning),
+      50..=59 => Some(Level::Error),
+      60..=69 => Some(Level::Fatal),
+      _ => None,
+    }
+  }
+
+  /// Logback's level values, as found in the `level_value` field of
+  /// logstash lines.
+  pub fn from_logback_value(number: i64) -> Option<Level> {
+    match number {
+      i64::MIN..=0 => None,
+      1..=9_999 => Some(Level::Trace),
+      10_000..=19_999 => Some(Level::Debug),
+      20_000..=29_999 => Some(Level::Info),
+      30_000..=39_999 => Some(Level::Warning),
+      _ => Some(Level::Error),
+    }
+  }
+
+  /// Accepts names as well as numbers, written as text or not.
+  pub fn from_json(value: &Value) -> Option<Level> {
+    match value {
+      Value::String(text) => text.parse().ok(),
+      Value::Number(number) => number.as_i64().and_then(Level::from_number),
+      _ => None,
+    }
+  }
+}
+
+/// Levels between the named ones are written by slog as an offset from them,
+/// like `INFO+2` or `DEBUG-4`. They are taken as the named level.
+pub fn without_offset(level: &str) -> &str {
+  match level.find(['+', '-']) {
+    Some(at) if level.len() > at + 1 && level[at + 1..].bytes().all(|b| b.is_ascii_digit()) => {
+      &level[..at]
+    }
+    _ => level,
+  }
+}
+
+impl FromStr for Level {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    Level::from_name(without_offset(text.trim()))
+      .or_else(|| text.trim().parse::<i64>().ok().and_then(Level::from_number))
+      .ok_or_else(|| {
+        format!(
+          "Unknown level '{text}', expected one of trace, debug, info, notice, warn, error, \
+           critical, fatal, alert, emergency, or a numeric level"
+        )
+      })
+  }
+}
+
+pub fn level_of(entry: &Value) -> Option<Level> {
+  lazy_static! {
+    static ref LEVEL_PATHS: Vec<FieldPath> = LEVEL_KEYS
+      .iter()
+      .map(|key| FieldPath::parse(key).unwrap())
+      .collect();
+  }
+
+  LEVEL_PATHS
+    .iter()
+    .filter_map(|path| path.get(entry))
+    .find_map(Level::from_json)
+    .or_else(|| {
+      entry
+        .get("level_value")
+        .and_then(Value::as_i64)
+        .and_then(Level::from_logback_value)
+    })
+    .or_else(|| {
+      // CLEF lines leave out the Information level.
+      let is_clef = entry.get("@t").is_some()
+        && (entry.get("@mt").is_some() || entry.get("@m").is_some())
+        && entry.get("@l").is_none();
+      is_clef.then_some(Level::Info)
+    })
+}
+
+/// Lines without a recognizable level don't pass.
+pub fn passes_min_level(min_level: Option<Level>, entry: &Value) -> bool {
+  match min_level {
+    None => true,
+    Some(min_level) => level_of(entry).is_some_and(|level| level >= min_level),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_level() {
+    assert_eq!("WARN".parse(), Ok(Level::Warning));
+    assert_eq!("Warning".parse(), Ok(Level::Warning));
+    assert_eq!("err".parse(), Ok(Level::Error));
+    assert_eq!("Information".parse(), Ok(Level::Info));
+    assert_eq!("50".parse(), Ok(Level::Error));
+    assert_eq!("4".parse(), Ok(Level::Warning));
+    assert_eq!("ERROR+2".parse(), Ok(Level::Error));
+    assert_eq!("INFO-4".parse(), Ok(Level::Info));
+    assert!("loud".parse::<Level>().is_err());
+    assert!("INFO+x".parse::<Level>().is_err());
+    assert!("100".parse::<Level>().is_err());
+  }
+
+  #[test]
+  fn test_level_names_are_understood() {
+    for level in [
+      Level::Trace,
+      Level::Notice,
+      Level::Warning,
+      Level::Emergency,
+    ] {
+      assert_eq!(level.name().parse(), Ok(level));
+    }
+    assert_eq!(Level::from_number(40).map(Level::name), Some("warn"));
+  }
+
+  #[test]
+  fn test_levels_are_ordered() {
+    assert!(Level::Trace < Level::Debug);
+    assert!(Level::Warning < Level::Error);
+    assert!(Level::Error < Level::Critical);
+    assert!(Level::Fatal < Level::Emergency);
+  }
+
+  #[test]
+  fn test_level_of() {
+    assert_eq!(level_of(&json!({"level": "INFO"})), Some(Level::Info));
+    assert_eq!(level_of(&json!({"level": 50})), Some(Level::Error));
+    assert_eq!(level_of(&json!({"level": "30"})), Some(Level::Info));
+    assert_eq!(level_of(&json!({"level": 3})), Some(Level::Error));
+    assert_eq!(
+      level_of(&json!({"severity": "WARNING"})),
+      Some(Level::Warning)
+    );
+    assert_eq!(level_of(&json!({"log.level": "debug"})), Some(Level::Debug));
+    assert_eq!(
+      level_of(&json!({"log": {"level": "trace"}})),
+      Some(Level::Trace)
+    );
+    assert_eq!(
+      level_of(&json!({"level_value": 30000})),
+      Some(Level::Warning)
+    );
+    assert_eq!(
+      level_of(&json!({"level": "custom", "level_value": 40000})),
+      Some(Level::Error)
+    );
+    assert_eq!(level_of(&json!({"@l": "Warning"})), Some(Level::Warning));
+    assert_eq!(
+      level_of(&json!({"@t": "2024-05-01T10:52:00Z", "@mt": "Started"})),
+      Some(Level::Info)
+    );
+    assert_eq!(level_of(&json!({"message": "no level"})), None);
+  }
+
+  #[test]
+  fn test_passes_min_level() {
+    let warn = Some(Level::Warning);
+    assert!(passes_min_level(warn, &json!({"level": "warning"})));
+    assert!(passes_min_level(warn, &json!({"level": "ERROR"})));
+    assert!(passes_min_level(warn, &json!({"level": 60})));
+    assert!(!passes_min_level(warn, &json!({"level": "info"})));
+    assert!(!passes_min_level(warn, &json!({"level": 30})));
+    assert!(!passes_min_level(warn, &json!({"level": 6})));
+    assert!(passes_min_level(warn, &json!({"level": "ERROR+2"})));
+    assert!(!passes_min_level(warn, &json!({"level": "INFO-4"})));
+    assert!(!passes_min_level(warn, &json!({"message": "no level"})));
+    assert!(passes_min_level(None, &json!({"message": "no level"})));
+  }
+}
@@ -8,61 +8,229 @@ extern crate serde;
 extern crate serde_json;
 
 use serde_json::Value;
-use std::fs::File;
 use std::io;
-use std::io::Write;
-use std::io::{BufRead, BufReader};
 
+pub mod container;
+pub mod expression;
 pub mod filter;
 pub mod format;
+pub mod input;
+pub mod level;
 pub mod line_formats;
+pub mod logfmt;
+pub mod multiline;
+pub mod non_json;
+pub mod output;
+pub mod path;
+pub mod search;
+pub mod source;
+pub mod time;
 
+use expression::*;
 use filter::*;
 use format::*;
+use input::*;
+use level::*;
+use non_json::*;
+use output::*;
+use search::*;
+use source::*;
+use time::*;
 
-pub fn read_log(
-  maybe_file_path: Option<&String>,
-  unparsed_filters: Vec<&str>,
-  number_of_lines: Option<&u64>,
-) {
-  let stdin = io::stdin();
-  let reader: Box<dyn BufRead> = match maybe_file_path {
-    Some(file_path) => Box::new(BufReader::new(
-      File::open(file_path).expect("File should exist"),
-    )),
-    None => Box::new(stdin.lock()),
-  };
-
-  let filters = parse_filters(unparsed_filters);
+#[derive(Default)]
+pub struct Options {
+  /// Files or glob patterns to read. Stdin is read when empty.
+  pub input_files: Vec<String>,
+  pub filters: Vec<Filter>,
+  pub where_expression: Option<Expression>,
+  pub number_of_lines: Option<u64>,
+  /// Only show lines at or above this level.
+  pub min_level: Option<Level>,
+  pub time_range: TimeRange,
+  /// Text or regex to find anywhere in the line, highlighted in the output.
+  pub search: Option<Search>,
+  pub non_json: NonJson,
+  /// Number of unmatched lines to show before each match.
+  pub before_context: usize,
+  /// Number of unmatched lines to show after each match.
+  pub after_context: usize,
+  pub follow: bool,
+  /// Prefix each line with the name of the file it was read from.
+  pub source_prefix: bool,
+}
+
+impl Options {
+  fn passes(&self, entry: &Value) -> bool {
+    passes_filters(&self.filters, entry)
+      && self
+        .where_expression
+        .as_ref()
+        .is_none_or(|expression| expression.passes(entry))
+      && passes_min_level(self.min_level, entry)
+  }
+
+  fn passes_text(&self, line: &str) -> bool {
+    passes_text_filters(&self.filters, line)
+      && self
+        .where_expression
+        .as_ref()
+        .is_none_or(|expression| expression.passes_text(line).unwrap_or(true))
+  }
+
+  // Whether lines can be left out by something else than the `--search`.
+  fn is_filtering(&self) -> bool {
+    !self.filters.is_empty()
+      || self.where_expression.is_some()
+      || self.min_level.is_some()
+      || self.time_range.since.is_some()
+      || self.time_range.until.is_some()
+  }
+
+  fn matches(&self, record: &LogRecord) -> bool {
+    let found = self
+      .search
+      .as_ref()
+      .is_none_or(|search| search.is_match(&record.line, record.json.as_ref()));
+    match (record.fields(), self.non_json) {
+      (Some(_), NonJson::Only) => false,
+      (Some(entry), _) => found && self.passes(&entry) && self.time_range.passes(record),
+      (None, NonJson::Hide) => false,
+      (None, NonJson::Search) => found && self.passes_text(&record.line),
+      (None, NonJson::Show)
+        if self.before_context + self.after_context > 0 && self.is_filtering() =>
+      {
+        self.search.is_some() && found
+      }
+      (None, NonJson::Show | NonJson::Only) => found,
+    }
+  }
+}
+
+pub fn read_log(options: Options) -> io::Result<()> {
+  let paths = expand_input_paths(&options.input_files)?;
+  let records = open_records(&paths, options.follow)?;
+
+  let prefix_width = paths.iter().map(|path| path.chars().count()).max();
 
   let mut count: u64 = 0;
 
   let stdout = io::stdout();
-  let mut stdout_lock = stdout.lock();
-  let mut formatter = Formatter::new();
-  for maybe_line in reader.lines() {
-    if number_of_lines.is_some() && count >= *number_of_lines.unwrap() {
-      return;
+  let mut printer = Printer::new(stdout.lock(), options.before_context, options.after_context);
+  let highlight = options
+    .search
+    .clone()
+    .filter(|_| colored::control::SHOULD_COLORIZE.should_colorize());
+  let mut formatter = Formatter::with_highlight(highlight);
+  for maybe_record in records {
+    if options.number_of_lines.is_some_and(|max| count >= max) {
+      return Ok(());
     }
-    let line = maybe_line.expect("Line should exist");
-    let output = match serde_json::from_str::<Value>(line.as_str()) {
-      Err(_e) => Some(formatter.format_not_json(&line)),
-      Ok(v) => {
-        if v.is_object() {
-          if passes_filters(&filters, &v) {
-            Some(formatter.format_message(v))
-          } else {
-            None
-          }
-        } else {
-          Some(formatter.format_not_json(&line))
+    let record = maybe_record?;
+    let matched = options.matches(&record);
+
+    printer.print(matched, || {
+      let source = record.source;
+      let text = formatter.format_record(record);
+      match prefix_width {
+        Some(width) if options.source_prefix => {
+          let prefix = format_source(&paths[source], width, source);
+          format!("{} {}", prefix, text)
         }
+        _ => text,
       }
-    };
-
-    if output.is_some() {
-      writeln!(stdout_lock, "{}", output.unwrap()).unwrap_or(());
+    });
+    if matched {
       count += 1;
     }
   }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn non_json_lines_are_context_around_matches() {
+    let banner = LogRecord::parse(0, "Starting worker".to_string());
+    let error = LogRecord::parse(0, r#"{"level":"error","message":"failed"}"#.to_string());
+    let mut options = Options {
+      filters: vec!["level=error".parse().unwrap()],
+      ..Options::default()
+    };
+    assert!(options.matches(&banner));
+    assert!(options.matches(&error));
+
+    options.before_context = 2;
+    assert!(!options.matches(&banner));
+    assert!(options.matches(&error));
+
+    options.search = Some("worker".parse().unwrap());
+    assert!(options.matches(&banner));
+  }
+
+  #[test]
+  fn filters_read_the_prefix_as_a_field() {
+    let options = |filter: &str| Options {
+      filters: vec![filter.parse().unwrap()],
+      ..Options::default()
+    };
+    let record = LogRecord::parse(
+      0,
+      r#"host app[1]: {"level":"info","message":"hi","_prefix":"mine"}"#.to_string(),
+    );
+    assert!(options("_prefix=+app[1]").matches(&record));
+    assert!(!options("_prefix=mine").matches(&record));
+    assert!(options("has:_prefix").matches(&record));
+    assert!(!options("missing:_prefix").matches(&record));
+    let where_prefix = Options {
+      where_expression: Some("_prefix~^host".parse().unwrap()),
+      ..Options::default()
+    };
+    assert!(where_prefix.matches(&record));
+    // The JSON itself keeps what the application logged.
+    assert_eq!(record.json.unwrap()["_prefix"], "mine");
+    assert!(
+      !options("has:_prefix").matches(&LogRecord::parse(0, r#"{"message":"hi"}"#.to_string()))
+    );
+  }
+
+  #[test]
+  fn filters_read_the_container_stream_and_time() {
+    let lines = [
+      r#"2024-01-01T00:00:00Z stdout F {"level":"info","message":"started"}"#,
+      r#"2024-01-01T00:00:01Z stderr F {"level":"error","message":"failed"}"#,
+    ]
+    .iter()
+    .map(|line| Ok(line.to_string()))
+    .collect::<Vec<io::Result<String>>>();
+    let records = read_records(0, Box::new(lines.into_iter()))
+      .map(|record| record.unwrap())
+      .collect::<Vec<LogRecord>>();
+    let matching = |filter: &str| {
+      let options = Options {
+        filters: vec![filter.parse().unwrap()],
+        ..Options::default()
+      };
+      records
+        .iter()
+        .filter(|record| options.matches(record))
+        .map(|record| record.line.as_str())
+        .collect::<Vec<&str>>()
+    };
+    assert_eq!(
+      matching("_stream=stderr"),
+      vec![r#"{"level":"error","message":"failed"}"#]
+    );
+    assert_eq!(
+      matching("_container_time=+00:00:00Z"),
+      vec![r#"{"level":"info","message":"started"}"#]
+    );
+    assert_eq!(matching("has:_stream").len(), 2);
+    // The JSON itself is left unchanged.
+    assert_eq!(
+      records[1].json,
+      Some(json!({ "level": "error", "message": "failed" }))
+    );
+  }
 }
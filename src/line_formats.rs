@@ -1,4 +1,6 @@
 use crate::format::colored_with_level;
+use crate::level::{without_offset, Level};
+use crate::time::{parse_timestamp, timestamp_from_epoch};
 use colored::*;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
@@ -9,10 +11,33 @@ pub trait FormatLogLine {
   fn format(&self) -> ColoredString;
 }
 
+// A format recognized from the JSON of a line, with how to show its lines and
+// where they keep their time.
+pub trait LogLine: FormatLogLine + Sized {
+  fn parse(entry: &Value) -> Option<Self>;
+  fn timestamp(&self) -> Option<DateTime<Utc>>;
+}
+
 pub trait ToColoredString {
   fn to_colored_string(entry: &Value) -> Option<ColoredString>;
 }
 
+impl<T: LogLine> ToColoredString for T {
+  fn to_colored_string(entry: &Value) -> Option<ColoredString> {
+    T::parse(entry).map(|line| line.format())
+  }
+}
+
+pub trait ToTimestamp {
+  fn to_timestamp(entry: &Value) -> Option<DateTime<Utc>>;
+}
+
+impl<T: LogLine> ToTimestamp for T {
+  fn to_timestamp(entry: &Value) -> Option<DateTime<Utc>> {
+    T::parse(entry).and_then(|line| line.timestamp())
+  }
+}
+
 fn format_mdc(mdc: &Map<String, Value>) -> String {
   if !mdc.is_empty() {
     let res = mdc
@@ -21,7 +46,8 @@ fn format_mdc(mdc: &Map<String, Value>) -> String {
       .map(|(key, value)| {
         let shown_value = match value {
           Value::String(val) => val,
-          other => format!("{other:?}"),
+          // Numbers, booleans, arrays and objects are shown as JSON.
+          other => other.to_string(),
         };
         format!("{key}={}", shown_value.trim())
       })
@@ -32,6 +58,65 @@ fn format_mdc(mdc: &Map<String, Value>) -> String {
   }
 }
 
+// The parts of the meta block that the line has, each between brackets.
+fn format_meta_parts(parts: &[Option<&str>]) -> String {
+  parts
+    .iter()
+    .flatten()
+    .map(|part| format!("[{part}]"))
+    .collect::<Vec<String>>()
+    .join(" ")
+}
+
+// Indented under the message, like the Java stack traces.
+fn format_stack_trace(stack_trace: Option<&str>) -> ColoredString {
+  match stack_trace {
+    Some(stack_trace) if !stack_trace.trim().is_empty() => {
+      format!("\n\t{}", stack_trace.trim_end().replace('\n', "\n\t")).red()
+    }
+    _ => "".normal(),
+  }
+}
+
+// Errors and tracebacks are usually text, but some loggers write them as
+// objects.
+fn error_text(error: &Option<Value>) -> Option<String> {
+  match error {
+    None | Some(Value::Null) => None,
+    Some(Value::String(text)) => Some(text.clone()),
+    Some(other) => Some(other.to_string()),
+  }
+}
+
+// The time as text, or as a number of seconds since the epoch, possibly with a
+// fraction. Larger numbers are taken as milli, micro or nanoseconds, like the
+// milliseconds Pino writes.
+fn timestamp_of_time(time: &Value) -> Option<DateTime<Utc>> {
+  match time {
+    Value::Number(number) => {
+      let mut seconds = number.as_f64()?;
+      while seconds.abs() >= 1e11 {
+        seconds /= 1000.0;
+      }
+      let whole = seconds.floor();
+      // Beyond microseconds, the fraction is noise from the float.
+      let micros = (((seconds - whole) * 1e6).round() as u32).min(999_999);
+      timestamp_from_epoch(whole as i64, micros * 1000)
+    }
+    Value::String(text) => parse_timestamp(text),
+    _ => None,
+  }
+}
+
+// Times written as numbers are shown as dates.
+fn format_time(time: &Value) -> String {
+  match (time, timestamp_of_time(time)) {
+    (Value::Number(_), Some(datetime)) => datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    (Value::String(text), _) => text.clone(),
+    (other, _) => other.to_string(),
+  }
+}
+
 //////////////////////////////////
 /// ElixirLogLine
 //////////////////////////////////
@@ -64,12 +149,13 @@ impl ElixirLogLine {
   }
 }
 
-impl ToColoredString for ElixirLogLine {
-  fn to_colored_string(entry: &Value) -> Option<ColoredString> {
-    match ElixirLogLine::deserialize(entry) {
-      Err(_) => None,
-      Ok(line) => Some(line.format()),
-    }
+impl LogLine for ElixirLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    ElixirLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.timestamp)
   }
 }
 
@@ -105,12 +191,13 @@ impl ElixirExtendedLogLine {
   }
 }
 
-impl ToColoredString for ElixirExtendedLogLine {
-  fn to_colored_string(entry: &Value) -> Option<ColoredString> {
-    match ElixirExtendedLogLine::deserialize(entry) {
-      Err(_) => None,
-      Ok(line) => Some(line.format()),
-    }
+impl LogLine for ElixirExtendedLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    ElixirExtendedLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.timestamp)
   }
 }
 
@@ -163,12 +250,13 @@ impl FormatLogLine for LogstashJavaLogLine {
   }
 }
 
-impl ToColoredString for LogstashJavaLogLine {
-  fn to_colored_string(entry: &Value) -> Option<ColoredString> {
-    match LogstashJavaLogLine::deserialize(entry) {
-      Err(_) => None,
-      Ok(line) => Some(line.format()),
-    }
+impl LogLine for LogstashJavaLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    LogstashJavaLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.timestamp)
   }
 }
 
@@ -266,12 +354,13 @@ impl FormatLogLine for Log4JJsonLayoutLogLine {
   }
 }
 
-impl ToColoredString for Log4JJsonLayoutLogLine {
-  fn to_colored_string(entry: &Value) -> Option<ColoredString> {
-    match Log4JJsonLayoutLogLine::deserialize(entry) {
-      Err(_) => None,
-      Ok(line) => Some(line.format()),
-    }
+impl LogLine for Log4JJsonLayoutLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    Log4JJsonLayoutLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    timestamp_from_epoch(self.instant.epoch_second, self.instant.nano_of_second)
   }
 }
 
@@ -309,12 +398,913 @@ impl Log4JJsonLayoutLogLine {
   }
 
   fn format_date(&self) -> String {
-    DateTime::from_timestamp(self.instant.epoch_second, self.instant.nano_of_second)
+    self
+      .timestamp()
       .map(|datetime: DateTime<Utc>| datetime.format("%+").to_string())
       .unwrap_or(self.instant.epoch_second.to_string())
   }
 }
 
+//////////////////////////////////
+/// Node.js: Bunyan and Pino
+//////////////////////////////////
+
+#[derive(Serialize, Deserialize)]
+pub struct BunyanLogLine {
+  // The version of Bunyan's format, which Pino doesn't write.
+  v: i64,
+  level: i64,
+  name: String,
+  hostname: String,
+  pid: i64,
+  time: String,
+  msg: String,
+  #[serde(default)]
+  err: Option<Value>,
+  // The fields added by the application.
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for BunyanLogLine {
+  fn format(&self) -> ColoredString {
+    let level = node_level_name(self.level);
+    colored_with_level(
+      &level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta(&level).dimmed(),
+        &self.msg,
+        &format_stack_trace(node_error_text(&self.err).as_deref())
+      ),
+    )
+  }
+}
+
+impl BunyanLogLine {
+  fn format_meta(&self, level: &str) -> String {
+    format!(
+      "[{}] [{}] [{}] [{}/{}]{}",
+      self.time,
+      level,
+      self.name,
+      self.hostname,
+      self.pid,
+      format_mdc(&self.fields)
+    )
+  }
+}
+
+impl LogLine for BunyanLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    BunyanLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.time)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PinoLogLine {
+  // Lines where Pino is configured to write labels aren't recognized.
+  level: i64,
+  time: Value,
+  // Like `pid` and `hostname`, written by default: required to tell Pino lines
+  // from other ones with a level and a time.
+  msg: String,
+  pid: i64,
+  hostname: String,
+  #[serde(default)]
+  name: Option<String>,
+  #[serde(default)]
+  err: Option<Value>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for PinoLogLine {
+  fn format(&self) -> ColoredString {
+    let level = node_level_name(self.level);
+    colored_with_level(
+      &level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta(&level).dimmed(),
+        &self.msg,
+        &format_stack_trace(node_error_text(&self.err).as_deref())
+      ),
+    )
+  }
+}
+
+impl PinoLogLine {
+  fn format_meta(&self, level: &str) -> String {
+    format_meta_parts(&[
+      Some(&format_time(&self.time)),
+      Some(level),
+      self.name.as_deref(),
+      Some(&format!("{}/{}", self.hostname, self.pid)),
+    ]) + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for PinoLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    PinoLogLine::deserialize(entry)
+      .ok()
+      .filter(|line| timestamp_of_time(&line.time).is_some())
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    timestamp_of_time(&self.time)
+  }
+}
+
+// Levels are numbers (30 is info), shown by their name.
+fn node_level_name(level: i64) -> String {
+  match Level::from_number(level) {
+    Some(known) => known.name().to_string(),
+    None => level.to_string(),
+  }
+}
+
+// The `err` of a record, with its stack when there is one.
+fn node_error_text(err: &Option<Value>) -> Option<String> {
+  let text = |key: &str| -> String {
+    err
+      .as_ref()
+      .and_then(|err| err.get(key))
+      .and_then(Value::as_str)
+      .unwrap_or_default()
+      .to_string()
+  };
+  let (stack, message) = (text("stack"), text("message"));
+  let name = Some(text("name"))
+    .filter(|name| !name.is_empty())
+    .unwrap_or_else(|| text("type"));
+  match err {
+    Some(Value::String(message)) => Some(message.clone()),
+    _ if !stack.is_empty() => Some(stack),
+    _ if !message.is_empty() && !name.is_empty() => Some(format!("{name}: {message}")),
+    _ if !message.is_empty() => Some(message),
+    _ => None,
+  }
+}
+
+//////////////////////////////////
+/// Google Cloud Logging
+//////////////////////////////////
+
+// What applications write on GKE or Cloud Run, for the logging agent to read.
+#[derive(Serialize, Deserialize)]
+pub struct GcpStructuredLogLine {
+  severity: String,
+  message: String,
+  #[serde(default, alias = "timestamp")]
+  time: Option<String>,
+  #[serde(default, rename = "logging.googleapis.com/trace")]
+  trace: Option<String>,
+  #[serde(default, rename = "logging.googleapis.com/spanId")]
+  span_id: Option<String>,
+  #[serde(default, rename = "logging.googleapis.com/labels")]
+  labels: Map<String, Value>,
+  // Picked up by Error Reporting.
+  #[serde(default)]
+  stack_trace: Option<String>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for GcpStructuredLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.severity,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.message,
+        &format_stack_trace(self.stack_trace.as_deref())
+      ),
+    )
+  }
+}
+
+impl GcpStructuredLogLine {
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      self.time.as_deref(),
+      Some(&self.severity),
+      gcp_trace(&self.trace, &self.span_id).as_deref(),
+    ]) + &format_mdc(&self.labels)
+      + &format_mdc(&gcp_fields(&self.fields, &[]))
+  }
+}
+
+impl LogLine for GcpStructuredLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    GcpStructuredLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    self.time.as_deref().and_then(parse_timestamp)
+  }
+}
+
+// The entries exported by `gcloud logging read --format=json`.
+#[derive(Serialize, Deserialize)]
+pub struct GcpLogEntry {
+  #[serde(rename = "logName")]
+  log_name: String,
+  timestamp: String,
+  // Entries without a severity have the `DEFAULT` one.
+  #[serde(default)]
+  severity: Option<String>,
+  #[serde(default, rename = "textPayload")]
+  text_payload: Option<String>,
+  #[serde(default, rename = "jsonPayload")]
+  json_payload: Map<String, Value>,
+  #[serde(default, rename = "protoPayload")]
+  proto_payload: Map<String, Value>,
+  #[serde(default)]
+  resource: Map<String, Value>,
+  #[serde(default)]
+  trace: Option<String>,
+  #[serde(default, rename = "spanId")]
+  span_id: Option<String>,
+  #[serde(default)]
+  labels: Map<String, Value>,
+}
+
+impl FormatLogLine for GcpLogEntry {
+  fn format(&self) -> ColoredString {
+    let stack_trace = self.json_payload.get("stack_trace").and_then(Value::as_str);
+    colored_with_level(
+      self.severity(),
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.message(),
+        &format_stack_trace(stack_trace)
+      ),
+    )
+  }
+}
+
+impl GcpLogEntry {
+  fn severity(&self) -> &str {
+    self.severity.as_deref().unwrap_or("DEFAULT")
+  }
+
+  // Audit logs have no text: their method stands for it.
+  fn message(&self) -> &str {
+    self
+      .text_payload
+      .as_deref()
+      .or_else(|| self.json_payload.get("message").and_then(Value::as_str))
+      .or_else(|| self.proto_payload.get("methodName").and_then(Value::as_str))
+      .unwrap_or_default()
+  }
+
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      Some(&self.timestamp),
+      Some(self.severity()),
+      self.resource.get("type").and_then(Value::as_str),
+      Some(&self.log_id()),
+      gcp_trace(&self.trace, &self.span_id).as_deref(),
+    ]) + &format_mdc(&self.labels)
+      + &format_mdc(&gcp_fields(&self.json_payload, &["message", "stack_trace"]))
+  }
+
+  // `projects/<project>/logs/<log id>`, where the log id is URL-encoded.
+  fn log_id(&self) -> String {
+    match self.log_name.split_once("/logs/") {
+      Some((_, log_id)) => log_id.replace("%2F", "/").replace("%2f", "/"),
+      None => self.log_name.clone(),
+    }
+  }
+}
+
+impl LogLine for GcpLogEntry {
+  fn parse(entry: &Value) -> Option<Self> {
+    GcpLogEntry::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.timestamp)
+  }
+}
+
+// Traces are written as `projects/<project>/traces/<trace id>`: the id is
+// enough to tell them apart.
+fn gcp_trace(trace: &Option<String>, span_id: &Option<String>) -> Option<String> {
+  let trace = trace
+    .as_deref()
+    .map(|trace| trace.rsplit('/').next().unwrap_or(trace));
+  match (trace, span_id) {
+    (Some(trace), Some(span_id)) => Some(format!("{trace}/{span_id}")),
+    (Some(trace), None) => Some(trace.to_string()),
+    (None, Some(span_id)) => Some(format!("/{span_id}")),
+    (None, None) => None,
+  }
+}
+
+// The other `logging.googleapis.com/` fields are for the logging agent.
+fn gcp_fields(fields: &Map<String, Value>, skipped: &[&str]) -> Map<String, Value> {
+  fields
+    .iter()
+    .filter(|(key, _)| {
+      !key.starts_with("logging.googleapis.com/") && !skipped.contains(&key.as_str())
+    })
+    .map(|(key, value)| (key.clone(), value.clone()))
+    .collect()
+}
+
+//////////////////////////////////
+/// Elastic Common Schema (ECS)
+//////////////////////////////////
+
+// Read with its nested fields as flat keys, see `EcsLogLine::from_entry`.
+#[derive(Serialize, Deserialize)]
+pub struct EcsLogLine {
+  #[serde(rename = "@timestamp")]
+  timestamp: String,
+  #[serde(rename = "log.level")]
+  level: String,
+  #[serde(default)]
+  message: String,
+  #[serde(default, rename = "log.logger")]
+  logger: Option<String>,
+  #[serde(default, rename = "process.thread.name")]
+  thread_name: Option<String>,
+  #[serde(default, rename = "service.name")]
+  service_name: Option<String>,
+  #[serde(default, rename = "error.stack_trace")]
+  stack_trace: String,
+  #[serde(default, rename = "error.message")]
+  error_message: String,
+  #[serde(default, rename = "error.type")]
+  error_type: String,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+// Describe the logger and the process rather than the event: not shown.
+static ECS_HIDDEN_FIELDS: [&str; 6] = ["ecs.", "log.", "process.", "host.", "event.", "error."];
+
+impl FormatLogLine for EcsLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.message,
+        &self.format_stacktrace()
+      ),
+    )
+  }
+}
+
+impl LogLine for EcsLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    EcsLogLine::from_entry(entry)
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.timestamp)
+  }
+}
+
+impl EcsLogLine {
+  // ECS loggers write fields as nested objects (`{"log": {"level": "info"}}`),
+  // as keys with dots (`{"log.level": "info"}`), or a mix of both.
+  fn from_entry(entry: &Value) -> Option<EcsLogLine> {
+    let map = entry.as_object()?;
+    if !map.contains_key("@timestamp") {
+      return None;
+    }
+    let mut flat = Map::new();
+    flatten_keys("", map, &mut flat);
+    EcsLogLine::deserialize(Value::Object(flat)).ok()
+  }
+
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      Some(&self.timestamp),
+      Some(&self.level),
+      self.service_name.as_deref(),
+      self.logger.as_deref(),
+      self.thread_name.as_deref(),
+    ]) + &self.format_mdc()
+  }
+
+  fn format_stacktrace(&self) -> ColoredString {
+    let error = if !self.stack_trace.is_empty() {
+      Some(self.stack_trace.clone())
+    } else if !self.error_message.is_empty() && !self.error_type.is_empty() {
+      Some(format!("{} ({})", self.error_message, self.error_type))
+    } else if !self.error_message.is_empty() {
+      Some(self.error_message.clone())
+    } else {
+      None
+    };
+    format_stack_trace(error.as_deref())
+  }
+
+  fn format_mdc(&self) -> String {
+    let shown = self
+      .fields
+      .iter()
+      .filter(|(key, _)| {
+        !ECS_HIDDEN_FIELDS
+          .iter()
+          .any(|hidden| key.starts_with(hidden))
+      })
+      .map(|(key, value)| (key.clone(), value.clone()))
+      .collect();
+    format_mdc(&shown)
+  }
+}
+
+fn flatten_keys(prefix: &str, map: &Map<String, Value>, flat: &mut Map<String, Value>) {
+  for (key, value) in map {
+    let key = if prefix.is_empty() {
+      key.clone()
+    } else {
+      format!("{prefix}.{key}")
+    };
+    match value {
+      Value::Object(nested) if !nested.is_empty() => flatten_keys(&key, nested, flat),
+      _ => {
+        flat.insert(key, value.clone());
+      }
+    }
+  }
+}
+
+//////////////////////////////////
+/// Go: zap, zerolog, logrus and slog
+//////////////////////////////////
+
+#[derive(Serialize, Deserialize)]
+pub struct ZapLogLine {
+  level: String,
+  // Seconds since the epoch by default.
+  ts: Value,
+  #[serde(default)]
+  msg: String,
+  #[serde(default)]
+  logger: Option<String>,
+  #[serde(default)]
+  caller: Option<String>,
+  #[serde(default)]
+  stacktrace: Option<String>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for ZapLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.msg,
+        &format_stack_trace(self.stacktrace.as_deref())
+      ),
+    )
+  }
+}
+
+impl ZapLogLine {
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      Some(&format_time(&self.ts)),
+      Some(&self.level),
+      self.logger.as_deref(),
+      self.caller.as_deref(),
+    ]) + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for ZapLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    ZapLogLine::deserialize(entry)
+      .ok()
+      .filter(|line| timestamp_of_time(&line.ts).is_some())
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    timestamp_of_time(&self.ts)
+  }
+}
+
+// Told apart from logrus and slog by its `message`.
+#[derive(Serialize, Deserialize)]
+pub struct ZerologLogLine {
+  level: String,
+  time: Value,
+  message: String,
+  #[serde(default)]
+  caller: Option<String>,
+  #[serde(default)]
+  error: Option<String>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for ZerologLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.message,
+        &format_stack_trace(self.error.as_deref())
+      ),
+    )
+  }
+}
+
+impl ZerologLogLine {
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      Some(&format_time(&self.time)),
+      Some(&self.level),
+      self.caller.as_deref(),
+    ]) + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for ZerologLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    ZerologLogLine::deserialize(entry)
+      .ok()
+      .filter(|line| timestamp_of_time(&line.time).is_some())
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    timestamp_of_time(&self.time)
+  }
+}
+
+// logrus and slog write the same keys: logrus writes its levels in lower case,
+// slog in upper case.
+static LOGRUS_LEVELS: [&str; 7] = [
+  "panic", "fatal", "error", "warning", "info", "debug", "trace",
+];
+
+#[derive(Serialize, Deserialize)]
+pub struct LogrusLogLine {
+  level: String,
+  time: String,
+  msg: String,
+  #[serde(default)]
+  error: Option<String>,
+  // With `SetReportCaller(true)`.
+  #[serde(default)]
+  func: Option<String>,
+  #[serde(default)]
+  file: Option<String>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for LogrusLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.msg,
+        &format_stack_trace(self.error.as_deref())
+      ),
+    )
+  }
+}
+
+impl LogrusLogLine {
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      Some(&self.time),
+      Some(&self.level),
+      self.func.as_deref(),
+      self.file.as_deref(),
+    ]) + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for LogrusLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    LogrusLogLine::deserialize(entry)
+      .ok()
+      .filter(|line| LOGRUS_LEVELS.contains(&line.level.as_str()))
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.time)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SlogLogLine {
+  time: String,
+  level: String,
+  msg: String,
+  // With `AddSource`.
+  #[serde(default)]
+  source: Option<SlogSource>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SlogSource {
+  #[serde(default)]
+  function: String,
+  file: String,
+  line: i64,
+}
+
+impl FormatLogLine for SlogLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      self.base_level(),
+      &format!("{} {}", &self.format_meta().dimmed(), &self.msg),
+    )
+  }
+}
+
+impl SlogLogLine {
+  fn base_level(&self) -> &str {
+    without_offset(&self.level)
+  }
+
+  fn format_meta(&self) -> String {
+    let source = self
+      .source
+      .as_ref()
+      .map(|source| format!("{}:{}", source.file, source.line));
+    format_meta_parts(&[Some(&self.time), Some(&self.level), source.as_deref()])
+      + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for SlogLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    SlogLogLine::deserialize(entry)
+      .ok()
+      .filter(|line| is_slog_level(line.base_level()))
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.time)
+  }
+}
+
+fn is_slog_level(level: &str) -> bool {
+  matches!(level, "DEBUG" | "INFO" | "WARN" | "ERROR")
+}
+
+//////////////////////////////////
+/// Python: python-json-logger and structlog
+//////////////////////////////////
+
+// The fields depend on the format given to the logger. These are the ones of
+// the usual `%(asctime)s %(levelname)s %(name)s %(message)s`.
+#[derive(Serialize, Deserialize)]
+pub struct PythonJsonLogLine {
+  levelname: String,
+  message: String,
+  #[serde(default)]
+  asctime: Option<String>,
+  #[serde(default)]
+  name: Option<String>,
+  #[serde(default)]
+  exc_info: Option<Value>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for PythonJsonLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.levelname,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.message,
+        &format_stack_trace(error_text(&self.exc_info).as_deref())
+      ),
+    )
+  }
+}
+
+impl PythonJsonLogLine {
+  fn format_meta(&self) -> String {
+    format_meta_parts(&[
+      self.asctime.as_deref(),
+      Some(&self.levelname),
+      self.name.as_deref(),
+    ]) + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for PythonJsonLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    PythonJsonLogLine::deserialize(entry).ok()
+  }
+
+  // `asctime` is written like `2024-05-01 10:52:00,123` by default.
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    self
+      .asctime
+      .as_ref()
+      .and_then(|asctime| parse_timestamp(&asctime.replacen(',', ".", 1)))
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct StructlogLogLine {
+  event: String,
+  level: String,
+  // Text, or seconds since the epoch when `TimeStamper` has no format.
+  #[serde(default)]
+  timestamp: Option<Value>,
+  #[serde(default)]
+  logger: Option<String>,
+  #[serde(default)]
+  exception: Option<Value>,
+  #[serde(flatten)]
+  fields: Map<String, Value>,
+}
+
+impl FormatLogLine for StructlogLogLine {
+  fn format(&self) -> ColoredString {
+    colored_with_level(
+      &self.level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta().dimmed(),
+        &self.event,
+        &format_stack_trace(error_text(&self.exception).as_deref())
+      ),
+    )
+  }
+}
+
+impl StructlogLogLine {
+  fn format_meta(&self) -> String {
+    let timestamp = self.timestamp.as_ref().map(format_time);
+    format_meta_parts(&[
+      timestamp.as_deref(),
+      Some(&self.level),
+      self.logger.as_deref(),
+    ]) + &format_mdc(&self.fields)
+  }
+}
+
+impl LogLine for StructlogLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    StructlogLogLine::deserialize(entry).ok()
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    self.timestamp.as_ref().and_then(timestamp_of_time)
+  }
+}
+
+//////////////////////////////////
+/// Serilog's Compact Log Event Format (CLEF)
+//////////////////////////////////
+
+#[derive(Serialize, Deserialize)]
+pub struct ClefLogLine {
+  #[serde(rename = "@t")]
+  timestamp: String,
+  // The message template, rendered in `@m` by some formatters.
+  #[serde(default, rename = "@mt")]
+  template: Option<String>,
+  #[serde(default, rename = "@m")]
+  message: Option<String>,
+  // Left out when it is Information.
+  #[serde(default = "clef_default_level", rename = "@l")]
+  level: String,
+  #[serde(default, rename = "@x")]
+  exception: Option<String>,
+  // The logger, set by `ForContext<T>()`.
+  #[serde(default, rename = "SourceContext")]
+  source_context: Option<String>,
+  #[serde(flatten)]
+  properties: Map<String, Value>,
+}
+
+fn clef_default_level() -> String {
+  "Information".to_string()
+}
+
+impl FormatLogLine for ClefLogLine {
+  fn format(&self) -> ColoredString {
+    let (message, used) = match (&self.message, &self.template) {
+      (Some(message), Some(template)) => (
+        message.clone(),
+        render_template(template, &self.properties).1,
+      ),
+      (Some(message), None) => (message.clone(), vec![]),
+      (None, Some(template)) => render_template(template, &self.properties),
+      (None, None) => ("".to_string(), vec![]),
+    };
+    let exception = format_stack_trace(self.exception.as_deref());
+    colored_with_level(
+      &self.level,
+      &format!(
+        "{} {}{}",
+        &self.format_meta(&used).dimmed(),
+        message,
+        exception
+      ),
+    )
+  }
+}
+
+impl ClefLogLine {
+  // The properties found in the message aren't repeated.
+  fn format_meta(&self, used: &[String]) -> String {
+    let properties = self
+      .properties
+      .iter()
+      .filter(|(key, _)| !key.starts_with('@') && !used.contains(key))
+      .map(|(key, value)| (key.clone(), value.clone()))
+      .collect();
+    format_meta_parts(&[
+      Some(&self.timestamp),
+      Some(&self.level),
+      self.source_context.as_deref(),
+    ]) + &format_mdc(&properties)
+  }
+}
+
+impl LogLine for ClefLogLine {
+  fn parse(entry: &Value) -> Option<Self> {
+    ClefLogLine::deserialize(entry)
+      .ok()
+      .filter(|line| line.template.is_some() || line.message.is_some())
+  }
+
+  fn timestamp(&self) -> Option<DateTime<Utc>> {
+    parse_timestamp(&self.timestamp)
+  }
+}
+
+// Fills in a message template (`User {UserId} logged in`) with the
+// properties, and gives the names of the ones it used. Holes are written
+// `{Name}`, `{@Name}` or `{$Name}`, with an optional `,alignment` and
+// `:format`, which are ignored. Holes without a property are kept as they are.
+fn render_template(template: &str, properties: &Map<String, Value>) -> (String, Vec<String>) {
+  let mut rendered = String::new();
+  let mut used = Vec::new();
+  let mut rest = template;
+  while let Some(position) = rest.find(['{', '}']) {
+    rendered.push_str(&rest[..position]);
+    let (brace, after) = rest[position..].split_at(1);
+    if after.starts_with(brace) {
+      // `{{` and `}}` stand for a brace.
+      rendered.push_str(brace);
+      rest = &after[1..];
+      continue;
+    }
+    let hole = match after.find('}') {
+      Some(end) if brace == "{" => &after[..end],
+      _ => {
+        rendered.push_str(brace);
+        rest = after;
+        continue;
+      }
+    };
+    let name = hole
+      .trim_start_matches(['@', '$'])
+      .split([',', ':'])
+      .next()
+      .unwrap_or_default();
+    match properties.get(name) {
+      Some(Value::String(text)) => rendered.push_str(text),
+      Some(value) => rendered.push_str(&value.to_string()),
+      None => rendered.push_str(&format!("{{{hole}}}")),
+    }
+    if properties.contains_key(name) {
+      used.push(name.to_string());
+    }
+    rest = &after[hole.len() + 1..];
+  }
+  rendered.push_str(rest);
+  (rendered, used)
+}
+
 // {
 //   "epochSecond": 1622724607,
 //   "nanoOfSecond": 420000000
@@ -409,4 +1399,34 @@ mod tests {
 
     assert_eq!(actual, expected);
   }
+
+  #[test]
+  fn test_render_template() {
+    let properties = json!({
+      "User": "ana",
+      "Elapsed": 34.5,
+      "Order": { "Id": 7 },
+      "0": "first"
+    });
+    let properties = properties.as_object().unwrap();
+    assert_eq!(
+      render_template(
+        "{User} took {Elapsed:0.0} ms for {@Order}, {$Missing,10} {0}",
+        properties
+      ),
+      (
+        r#"ana took 34.5 ms for {"Id":7}, {$Missing,10} first"#.to_string(),
+        vec![
+          "User".to_string(),
+          "Elapsed".to_string(),
+          "Order".to_string(),
+          "0".to_string()
+        ]
+      )
+    );
+    assert_eq!(
+      render_template("{{literal}} } {unclosed", properties),
+      ("{literal} } {unclosed".to_string(), vec![])
+    );
+  }
 }
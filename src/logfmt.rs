@@ -0,0 +1,94 @@
+use serde_json::{Map, Value};
+
+/// Reads a logfmt line (`level=info ts=... msg="started" component=api`) as
+/// an object, so that it is shown and filtered like a JSON line. Values are
+/// kept as text. Every part of the line must be a `key=value` pair, so that
+/// plain text isn't taken for logfmt.
+pub fn parse_logfmt(line: &str) -> Option<Value> {
+  let mut map = Map::new();
+  let mut chars = line.chars().peekable();
+  loop {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    if chars.peek().is_none() {
+      break;
+    }
+
+    let mut key = String::new();
+    while let Some(c) = chars.next_if(|&c| c != '=' && c != '"' && !c.is_whitespace()) {
+      key.push(c);
+    }
+    if key.is_empty() || chars.next() != Some('=') {
+      return None;
+    }
+
+    let mut value = String::new();
+    if chars.next_if_eq(&'"').is_some() {
+      loop {
+        match chars.next()? {
+          '"' => break,
+          '\\' => value.push(match chars.next()? {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            other => other,
+          }),
+          c => value.push(c),
+        }
+      }
+      if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+        return None;
+      }
+    } else {
+      while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+        if c == '"' {
+          return None;
+        }
+        value.push(c);
+      }
+    }
+    map.insert(key, Value::String(value));
+  }
+
+  if map.is_empty() {
+    None
+  } else {
+    Some(Value::Object(map))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_logfmt() {
+    assert_eq!(
+      parse_logfmt(
+        r#"level=info ts=2024-05-01T10:52:00Z msg="started \"api\"\tok" component=api err= a.b=c=d"#
+      ),
+      Some(json!({
+        "level": "info",
+        "ts": "2024-05-01T10:52:00Z",
+        "msg": "started \"api\"\tok",
+        "component": "api",
+        "err": "",
+        "a.b": "c=d"
+      }))
+    );
+    assert_eq!(
+      parse_logfmt("  status=500  "),
+      Some(json!({"status": "500"}))
+    );
+  }
+
+  #[test]
+  fn test_plain_text_is_not_logfmt() {
+    assert_eq!(parse_logfmt("Starting server on port 8080"), None);
+    assert_eq!(parse_logfmt("Retrying with timeout=30s"), None);
+    assert_eq!(parse_logfmt("=value"), None);
+    assert_eq!(parse_logfmt(r#"msg="unclosed"#), None);
+    assert_eq!(parse_logfmt(r#"msg="quoted"text"#), None);
+    assert_eq!(parse_logfmt(r#"msg=bare"quote"#), None);
+    assert_eq!(parse_logfmt(""), None);
+  }
+}
@@ -1,11 +1,21 @@
 extern crate clap;
 
+use chrono::{DateTime, Utc};
 use clap::{Arg, Command};
-use jaslog::read_log;
+use jaslog::expression::Expression;
+use jaslog::filter::Filter;
+use jaslog::level::Level;
+use jaslog::non_json::NonJson;
+use jaslog::search::Search;
+use jaslog::time::{parse_time_bound, TimeRange};
+use jaslog::{read_log, Options};
 
 fn main() {
   const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+  let now = Utc::now();
+  let time_bound_parser = move |text: &str| parse_time_bound(text, now);
+
   let options = Command::new("jaslog")
     .version(VERSION)
     .about("JSON logs reader for JSON logs")
@@ -13,9 +23,70 @@ fn main() {
     .arg(Arg::new("filters")
       .short('f')
       .long("filter")
-      .help("Filter the logs. Example:  -f app=this -f module=+Drive (use '+' to search within the field, use '^' to exclude within the field)")
+      .help("Filter the logs. Example:  -f app=this -f module=+Drive -f message~^Timeout -f status>=500 (use '+' to search within the field, use '^' to exclude within the field, '~' to match a regex, '!~' to exclude a regex, '!=', '>', '>=', '<', '<=' to compare, has:<field> and missing:<field> to check whether a field is there, 'in' to look for a list of values: 'level in (error, fatal)'. Add ':i' after the field to ignore case: level:i=info)")
+      .value_parser(clap::value_parser!(Filter))
       .action(clap::ArgAction::Append))
 
+    .arg(Arg::new("where")
+      .short('w')
+      .long("where")
+      .help("Filter the logs with an expression combining filters with and, or, not and parentheses. Example:  --where 'level=error or (level=warn and module=+Kafka)'")
+      .value_parser(clap::value_parser!(Expression))
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("search")
+      .short('s')
+      .long("search")
+      .help("Only show lines containing this text anywhere (in any field, or in lines that aren't JSON), and highlight it. Write it as /regex/ to search for a regex, or /regex/i to ignore case.")
+      .value_parser(clap::value_parser!(Search))
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("non_json")
+      .long("non-json")
+      .help("What to do with the lines that aren't JSON: show them whatever the filters (default), hide them, only show them, or search them with --search and the filters searching text (=+, =^, ~, !~), the other filters being ignored.")
+      .value_parser(clap::value_parser!(NonJson))
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("min_level")
+      .short('l')
+      .long("level")
+      .help("Only show lines at or above this level (e.g. warn). Synonyms (warning, err...) and numeric levels (Bunyan/Pino, syslog) are understood.")
+      .value_parser(clap::value_parser!(Level))
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("since")
+      .long("since")
+      .help("Only show lines logged at or after this time: a timestamp (2024-05-01T10:52:00Z), a date (2024-05-01), a time of today (10:52) or a duration (15m ago). Times without an offset are taken as UTC.")
+      .value_parser(time_bound_parser)
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("until")
+      .long("until")
+      .help("Only show lines logged at or before this time, in the same formats as --since.")
+      .value_parser(time_bound_parser)
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("after_context")
+      .short('A')
+      .long("after-context")
+      .help("Show this number of lines after each matching line.")
+      .value_parser(clap::value_parser!(usize))
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("before_context")
+      .short('B')
+      .long("before-context")
+      .help("Show this number of lines before each matching line.")
+      .value_parser(clap::value_parser!(usize))
+      .action(clap::ArgAction::Set))
+
+    .arg(Arg::new("context")
+      .short('C')
+      .long("context")
+      .help("Show this number of lines before and after each matching line.")
+      .value_parser(clap::value_parser!(usize))
+      .action(clap::ArgAction::Set))
+
     .arg(Arg::new("number_of_lines")
       .short('n')
       .long("lines")
@@ -24,23 +95,87 @@ fn main() {
       .value_parser(clap::value_parser!(u64))
       .action(clap::ArgAction::Set))
 
+    .arg(Arg::new("follow")
+      .short('F')
+      .long("follow")
+      .help("Keep reading the input files as they grow, like `tail -F` (survives log rotation).")
+      .requires("input_files")
+      .action(clap::ArgAction::SetTrue))
 
-    .arg(Arg::new("input_file")
-      .help("Input file to read")
+    .arg(Arg::new("source_prefix")
+      .short('p')
+      .long("prefix")
+      .help("Prefix each line with the name of the file it comes from.")
+      .action(clap::ArgAction::SetTrue))
+
+    .arg(Arg::new("input_files")
+      .help("Input files to read (glob patterns are expanded). When there are several, their lines are merged by timestamp.")
       .required(false)
       .index(1)
-      .action(clap::ArgAction::Set))
+      .num_args(1..)
+      .action(clap::ArgAction::Append))
 
     .get_matches();
 
-  let file_path = options.get_one::<String>("input_file");
+  let input_files: Vec<String> = options
+    .get_many::<String>("input_files")
+    .map(|files| files.cloned().collect())
+    .unwrap_or_default();
+
+  let number_of_lines = options.get_one::<u64>("number_of_lines").copied();
+
+  let filters: Vec<Filter> = options
+    .get_many::<Filter>("filters")
+    .map(|filters| filters.cloned().collect())
+    .unwrap_or_default();
+
+  let where_expression = options.get_one::<Expression>("where").cloned();
 
-  let lines = options.get_one::<u64>("number_of_lines");
+  let search = options.get_one::<Search>("search").cloned();
 
-  let filters: Vec<&str> = options
-    .get_many::<String>("filters")
-    .map(|filters| filters.clone().map(|s| s.as_str()).collect())
+  let non_json = options
+    .get_one::<NonJson>("non_json")
+    .copied()
     .unwrap_or_default();
 
-  read_log(file_path, filters, lines);
+  let min_level = options.get_one::<Level>("min_level").copied();
+
+  let time_range = TimeRange {
+    since: options.get_one::<DateTime<Utc>>("since").copied(),
+    until: options.get_one::<DateTime<Utc>>("until").copied(),
+  };
+
+  let context = options.get_one::<usize>("context").copied().unwrap_or(0);
+  let before_context = options
+    .get_one::<usize>("before_context")
+    .copied()
+    .unwrap_or(context);
+  let after_context = options
+    .get_one::<usize>("after_context")
+    .copied()
+    .unwrap_or(context);
+
+  let follow = options.get_flag("follow");
+
+  let source_prefix = options.get_flag("source_prefix");
+
+  let result = read_log(Options {
+    input_files,
+    filters,
+    where_expression,
+    number_of_lines,
+    min_level,
+    time_range,
+    search,
+    non_json,
+    before_context,
+    after_context,
+    follow,
+    source_prefix,
+  });
+
+  if let Err(e) = result {
+    eprintln!("jaslog: {e}");
+    std::process::exit(1);
+  }
 }
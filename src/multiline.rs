@@ -0,0 +1,314 @@
+use serde_json::Value;
+use std::collections::VecDeque;
+
+// A document still incomplete after this many lines is taken as plain text.
+const MAX_DOCUMENT_LINES: usize = 1_000;
+
+#[derive(Debug, PartialEq)]
+pub enum Assembled {
+  /// A JSON object, with its text.
+  Object(Value, String),
+  Plain(String),
+}
+
+/// Reads JSON documents spanning several lines: pretty-printed objects, one
+/// after the other or in a top-level array. Lines that turn out not to be part
+/// of a document are given back as they are.
+///
+/// The document is read again with serde as its lines come: it stays pending
+/// while its text only ends too early, and is given up on any other error.
+#[derive(Default)]
+pub struct Assembler {
+  // The text of the document being read, from the end of the last value
+  // read in it. Every line in it ends with a newline.
+  buffer: String,
+  in_array: bool,
+}
+
+fn starts_document(line: &str) -> bool {
+  line.trim_start().starts_with(['{', '['])
+}
+
+enum Read {
+  Value(Value, usize),
+  // The text ends before the value does.
+  Incomplete,
+  Invalid,
+}
+
+fn read_value(text: &str) -> Read {
+  let mut values = serde_json::Deserializer::from_str(text).into_iter::<Value>();
+  match values.next() {
+    Some(Ok(value)) => Read::Value(value, values.byte_offset()),
+    Some(Err(error)) if error.is_eof() => Read::Incomplete,
+    None => Read::Incomplete,
+    Some(Err(_)) => Read::Invalid,
+  }
+}
+
+impl Assembler {
+  pub fn push(&mut self, line: String) -> Vec<Assembled> {
+    let mut assembled = Vec::new();
+    let mut lines = VecDeque::from(vec![line]);
+    while let Some(line) = lines.pop_front() {
+      if !self.push_line(line, &mut assembled) {
+        // Read again the lines of the document given up, but its first one.
+        for line in self.give_up(&mut assembled).into_iter().rev() {
+          lines.push_front(line);
+        }
+      }
+    }
+    assembled
+  }
+
+  /// Gives back the lines of a document left incomplete.
+  pub fn flush(&mut self) -> Vec<Assembled> {
+    let mut assembled = Vec::new();
+    while self.collecting() {
+      for line in self.give_up(&mut assembled) {
+        assembled.extend(self.push(line));
+      }
+    }
+    assembled
+  }
+
+  fn collecting(&self) -> bool {
+    !self.buffer.is_empty() || self.in_array
+  }
+
+  // False when the line can't be part of the document being read.
+  fn push_line(&mut self, line: String, assembled: &mut Vec<Assembled>) -> bool {
+    if !self.collecting() {
+      match serde_json::from_str::<Value>(&line) {
+        Ok(value @ Value::Object(_)) => {
+          assembled.push(Assembled::Object(value, line));
+          return true;
+        }
+        _ if !starts_document(&line) => {
+          assembled.push(Assembled::Plain(line));
+          return true;
+        }
+        _ => (),
+      }
+    }
+    // With the newline, a string left open on the line is an error.
+    self.buffer.push_str(&line);
+    self.buffer.push('\n');
+    self.read(assembled) && self.buffer.matches('\n').count() <= MAX_DOCUMENT_LINES
+  }
+
+  // Reads the values completed in the buffer, and drops their text from it.
+  fn read(&mut self, assembled: &mut Vec<Assembled>) -> bool {
+    loop {
+      // What separates the values goes with the one before.
+      let separators: &[char] = if self.in_array {
+        &[',', ' ', '\t', '\r', '\n']
+      } else {
+        &[' ', '\t', '\r', '\n']
+      };
+      let skipped = self.buffer.len() - self.buffer.trim_start_matches(separators).len();
+      self.buffer.drain(..skipped);
+
+      let rest = self.buffer.as_str();
+      match rest.chars().next() {
+        None => return true,
+        Some(']') if self.in_array => {
+          self.in_array = false;
+          self.buffer.drain(..1);
+          continue;
+        }
+        Some('[') if !self.in_array => {
+          // An array of records when an object comes next, a single value
+          // otherwise.
+          match rest[1..].trim_start().chars().next() {
+            None => return true,
+            Some('{' | ']') => {
+              self.in_array = true;
+              self.buffer.drain(..1);
+              continue;
+            }
+            Some(_) => (),
+          }
+        }
+        Some('{') => (),
+        Some(_) => return false,
+      }
+
+      match read_value(rest) {
+        Read::Incomplete => return true,
+        Read::Invalid => return false,
+        Read::Value(value, end) => {
+          let text = rest[..end].to_string();
+          assembled.push(match value {
+            Value::Object(_) => Assembled::Object(value, text),
+            _ => Assembled::Plain(text),
+          });
+          self.buffer.drain(..end);
+        }
+      }
+    }
+  }
+
+  // Takes the first line left as plain text, and gives back the next ones to
+  // be read again.
+  fn give_up(&mut self, assembled: &mut Vec<Assembled>) -> Vec<String> {
+    let text = std::mem::take(&mut self.buffer);
+    *self = Assembler::default();
+    let mut lines = text.strip_suffix('\n').unwrap_or(&text).split('\n');
+    if let Some(first) = lines.next().filter(|first| !first.trim().is_empty()) {
+      assembled.push(Assembled::Plain(first.to_string()));
+    }
+    lines.map(String::from).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn assembled(lines: &[&str]) -> Vec<Assembled> {
+    let mut assembler = Assembler::default();
+    let mut assembled: Vec<Assembled> = lines
+      .iter()
+      .flat_map(|line| assembler.push(line.to_string()))
+      .collect();
+    assembled.extend(assembler.flush());
+    assembled
+  }
+
+  fn object(value: Value, text: &str) -> Assembled {
+    Assembled::Object(value, text.to_string())
+  }
+
+  fn plain(text: &str) -> Assembled {
+    Assembled::Plain(text.to_string())
+  }
+
+  #[test]
+  fn reads_single_lines() {
+    assert_eq!(
+      assembled(&[r#"{"message":"a"}"#, "plain", ""]),
+      vec![
+        object(json!({"message": "a"}), r#"{"message":"a"}"#),
+        plain("plain"),
+        plain("")
+      ]
+    );
+  }
+
+  #[test]
+  fn reads_pretty_printed_objects() {
+    assert_eq!(
+      assembled(&[
+        "{",
+        r#"  "message": "a","#,
+        r#"  "level": "info""#,
+        "}{",
+        r#""message": "b"}"#
+      ]),
+      vec![
+        object(
+          json!({"message": "a", "level": "info"}),
+          "{\n  \"message\": \"a\",\n  \"level\": \"info\"\n}"
+        ),
+        object(json!({"message": "b"}), "{\n\"message\": \"b\"}"),
+      ]
+    );
+  }
+
+  #[test]
+  fn reads_arrays_of_records() {
+    assert_eq!(
+      assembled(&[
+        r#"[{"message": "a"},"#,
+        "  {",
+        r#"    "message": "b""#,
+        "  }",
+        "]",
+        "[1, 2]",
+        "[]"
+      ]),
+      vec![
+        object(json!({"message": "a"}), r#"{"message": "a"}"#),
+        object(json!({"message": "b"}), "{\n    \"message\": \"b\"\n  }"),
+        plain("[1, 2]"),
+      ]
+    );
+  }
+
+  #[test]
+  fn plain_text_is_given_back() {
+    assert_eq!(
+      assembled(&[
+        "[main] INFO starting",
+        "{",
+        "not json",
+        r#"{"message": "a"}"#
+      ]),
+      vec![
+        plain("[main] INFO starting"),
+        plain("{"),
+        plain("not json"),
+        object(json!({"message": "a"}), r#"{"message": "a"}"#),
+      ]
+    );
+    // Left incomplete at the end.
+    assert_eq!(
+      assembled(&["{", r#"  "message": "a","#, "plain"]),
+      vec![plain("{"), plain(r#"  "message": "a","#), plain("plain")]
+    );
+    assert_eq!(
+      assembled(&["[", r#"{"message": "a"}, {"#]),
+      vec![
+        object(json!({"message": "a"}), r#"{"message": "a"}"#),
+        plain("{")
+      ]
+    );
+  }
+
+  #[test]
+  fn reads_strings_holding_brackets() {
+    assert_eq!(
+      assembled(&["{", r#"  "message": "a } \" ] {""#, "}"]),
+      vec![object(
+        json!({"message": "a } \" ] {"}),
+        "{\n  \"message\": \"a } \\\" ] {\"\n}"
+      )]
+    );
+  }
+
+  #[test]
+  fn stray_brackets_dont_hold_lines_back() {
+    let mut assembler = Assembler::default();
+    assert_eq!(assembler.push("{".to_string()), vec![]);
+    assert_eq!(
+      assembler.push("[main] INFO started".to_string()),
+      vec![plain("{"), plain("[main] INFO started")]
+    );
+    assert_eq!(assembler.push("[".to_string()), vec![]);
+    assert_eq!(
+      assembler.push("Started in 2s".to_string()),
+      vec![plain("["), plain("Started in 2s")]
+    );
+    assert_eq!(
+      assembler.push(r#"{"message": "a"} done"#.to_string()),
+      vec![
+        object(json!({"message": "a"}), r#"{"message": "a"}"#),
+        plain("done")
+      ]
+    );
+    // Given up as soon as serde finds an error, before the document ends.
+    assert_eq!(assembler.push("{".to_string()), vec![]);
+    assert_eq!(
+      assembler.push(r#"  "a": tsunami,"#.to_string()),
+      vec![plain("{"), plain(r#"  "a": tsunami,"#)]
+    );
+    // A string can't go on to the next line.
+    assert_eq!(assembler.push("{".to_string()), vec![]);
+    assert_eq!(
+      assembler.push(r#"  "message": "unclosed"#.to_string()),
+      vec![plain("{"), plain(r#"  "message": "unclosed"#)]
+    );
+    assert_eq!(assembler.flush(), vec![]);
+  }
+}
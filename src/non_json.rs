@@ -0,0 +1,46 @@
+use std::str::FromStr;
+
+/// What to do with the lines that aren't JSON.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum NonJson {
+  /// Show them, whatever the filters. `--search` still applies. With context
+  /// around the matches of the filters, they are context like the other lines.
+  #[default]
+  Show,
+  Hide,
+  /// Only show them.
+  Only,
+  /// Apply `--search` and the filters searching text (`=+`, `=^`, `~`, `!~`)
+  /// to the whole line, and ignore the other ones.
+  Search,
+}
+
+impl FromStr for NonJson {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    match text {
+      "show" => Ok(NonJson::Show),
+      "hide" => Ok(NonJson::Hide),
+      "only" => Ok(NonJson::Only),
+      "search" => Ok(NonJson::Search),
+      _ => Err(format!(
+        "Unknown policy '{text}' for non-JSON lines, expected show, hide, only or search"
+      )),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_non_json() {
+    assert_eq!("show".parse(), Ok(NonJson::Show));
+    assert_eq!("hide".parse(), Ok(NonJson::Hide));
+    assert_eq!("only".parse(), Ok(NonJson::Only));
+    assert_eq!("search".parse(), Ok(NonJson::Search));
+    assert!("all".parse::<NonJson>().is_err());
+  }
+}
@@ -0,0 +1,165 @@
+use colored::*;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::io::Write;
+
+const GROUP_SEPARATOR: &str = "--";
+
+lazy_static! {
+  /// The color codes in a formatted line.
+  pub static ref ANSI_REGEX: Regex = Regex::new("\x1b\\[[0-9;]*m").unwrap();
+}
+
+/// Writes the matching lines, with the lines around them as context like
+/// `grep -B/-A`. Context lines are dimmed, and non-contiguous groups of lines
+/// are separated by `--`.
+pub struct Printer<W: Write> {
+  out: W,
+  before: usize,
+  after: usize,
+  // Unmatched lines that may be shown before the next match.
+  buffer: VecDeque<(u64, String)>,
+  // Number of unmatched lines still to show after the last match.
+  after_remaining: usize,
+  next_index: u64,
+  last_printed: Option<u64>,
+}
+
+impl<W: Write> Printer<W> {
+  pub fn new(out: W, before: usize, after: usize) -> Self {
+    Printer {
+      out,
+      before,
+      after,
+      buffer: VecDeque::with_capacity(before),
+      after_remaining: 0,
+      next_index: 0,
+      last_printed: None,
+    }
+  }
+
+  /// Takes the next line. It is only formatted if it is shown.
+  pub fn print<F: FnOnce() -> String>(&mut self, matched: bool, format: F) {
+    let index = self.next_index;
+    self.next_index += 1;
+
+    if matched {
+      self.print_separator_if_needed(self.buffer.front().map_or(index, |(first, _)| *first));
+      while let Some((buffered_index, text)) = self.buffer.pop_front() {
+        self.write_context(buffered_index, &text);
+      }
+      self.write(index, format());
+      self.after_remaining = self.after;
+    } else if self.after_remaining > 0 {
+      self.after_remaining -= 1;
+      self.write_context(index, &format());
+    } else if self.before > 0 {
+      if self.buffer.len() == self.before {
+        self.buffer.pop_front();
+      }
+      self.buffer.push_back((index, format()));
+    }
+  }
+
+  fn print_separator_if_needed(&mut self, first_index: u64) {
+    let has_context = self.before > 0 || self.after > 0;
+    match self.last_printed {
+      Some(last) if has_context && first_index > last + 1 => {
+        writeln!(self.out, "{}", GROUP_SEPARATOR.cyan()).unwrap_or(())
+      }
+      _ => (),
+    }
+  }
+
+  fn write_context(&mut self, index: u64, text: &str) {
+    self.write(index, format!("{}", strip_colors(text).dimmed()));
+  }
+
+  fn write(&mut self, index: u64, text: String) {
+    writeln!(self.out, "{}", text).unwrap_or(());
+    self.last_printed = Some(index);
+  }
+}
+
+// Context lines are dimmed as a whole, so their own colors are removed.
+fn strip_colors(text: &str) -> String {
+  ANSI_REGEX.replace_all(text, "").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // Feeds lines to a printer, the ones starting with '>' are matches.
+  fn printed(before: usize, after: usize, lines: &[&str]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut printer = Printer::new(&mut out, before, after);
+    for line in lines {
+      printer.print(line.starts_with('>'), || line.to_string());
+    }
+    String::from_utf8(out)
+      .unwrap()
+      .lines()
+      .map(String::from)
+      .collect()
+  }
+
+  fn context(text: &str) -> String {
+    format!("{}", text.dimmed())
+  }
+
+  fn separator() -> String {
+    format!("{}", GROUP_SEPARATOR.cyan())
+  }
+
+  #[test]
+  fn without_context_only_matches_are_printed() {
+    assert_eq!(
+      printed(0, 0, &["a", ">b", "c", "d", ">e"]),
+      vec![">b".to_string(), ">e".to_string()]
+    );
+  }
+
+  #[test]
+  fn context_before_and_after() {
+    assert_eq!(
+      printed(1, 1, &["a", "b", ">c", "d", "e", "f", ">g", "h"]),
+      vec![
+        context("b"),
+        ">c".to_string(),
+        context("d"),
+        separator(),
+        context("f"),
+        ">g".to_string(),
+        context("h"),
+      ]
+    );
+  }
+
+  #[test]
+  fn contiguous_groups_are_not_separated() {
+    assert_eq!(
+      printed(2, 1, &["a", ">b", "c", "d", ">e", ">f"]),
+      vec![
+        context("a"),
+        ">b".to_string(),
+        context("c"),
+        context("d"),
+        ">e".to_string(),
+        ">f".to_string(),
+      ]
+    );
+  }
+
+  #[test]
+  fn context_lines_lose_their_colors() {
+    let mut out = Vec::new();
+    let mut printer = Printer::new(&mut out, 1, 0);
+    printer.print(false, || format!("\x1b[31m{}\x1b[0m", "an error"));
+    printer.print(true, || "match".to_string());
+    assert_eq!(
+      String::from_utf8(out).unwrap(),
+      format!("{}\nmatch\n", "an error".dimmed())
+    );
+  }
+}
@@ -0,0 +1,230 @@
+use serde_json::Value;
+use std::fmt;
+
+/// Path to a field inside a JSON log line, for example `mdc.requestId`,
+/// `tags[0]` or `instant.epochSecond`.
+///
+/// Dots separate nested keys. Keys that contain dots or brackets can be
+/// escaped with a backslash (`logging\.googleapis\.com/trace`) or quoted
+/// between brackets (`["log.level"]`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct FieldPath {
+  text: String,
+  segments: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Segment {
+  Key(String),
+  Index(usize),
+}
+
+impl FieldPath {
+  pub fn parse(text: &str) -> Result<FieldPath, String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    // Set right after a `]`, where a `.` doesn't end a key.
+    let mut after_bracket = false;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+      match c {
+        '\\' => match chars.next() {
+          Some(escaped) => current.push(escaped),
+          None => return Err(format!("Trailing backslash in field path: {text}")),
+        },
+        '.' => {
+          if !current.is_empty() {
+            segments.push(Segment::Key(std::mem::take(&mut current)));
+          } else if !after_bracket {
+            return Err(format!("Empty key in field path: {text}"));
+          }
+        }
+        '[' => {
+          if !current.is_empty() {
+            segments.push(Segment::Key(std::mem::take(&mut current)));
+          }
+          segments.push(parse_bracket(&mut chars, text)?);
+          after_bracket = true;
+          continue;
+        }
+        other => current.push(other),
+      }
+      after_bracket = false;
+    }
+
+    if !current.is_empty() {
+      segments.push(Segment::Key(current));
+    } else if !after_bracket {
+      return Err(format!("Empty key in field path: {text}"));
+    }
+
+    Ok(FieldPath {
+      text: text.to_string(),
+      segments,
+    })
+  }
+
+  pub fn get<'a>(&self, entry: &'a Value) -> Option<&'a Value> {
+    lookup(entry, &self.segments)
+  }
+}
+
+impl fmt::Display for FieldPath {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.text)
+  }
+}
+
+fn parse_bracket(chars: &mut std::str::Chars, text: &str) -> Result<Segment, String> {
+  let unclosed = || format!("Unclosed '[' in field path: {text}");
+  let mut content = String::new();
+
+  if chars.as_str().starts_with('"') {
+    chars.next();
+    loop {
+      match chars.next().ok_or_else(unclosed)? {
+        '\\' => content.push(chars.next().ok_or_else(unclosed)?),
+        '"' => break,
+        other => content.push(other),
+      }
+    }
+    return match chars.next() {
+      Some(']') => Ok(Segment::Key(content)),
+      _ => Err(unclosed()),
+    };
+  }
+
+  loop {
+    match chars.next().ok_or_else(unclosed)? {
+      ']' => break,
+      other => content.push(other),
+    }
+  }
+  content
+    .trim()
+    .parse::<usize>()
+    .map(Segment::Index)
+    .map_err(|_| format!("Invalid array index '{content}' in field path: {text}"))
+}
+
+fn lookup<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+  let (first, rest) = match segments.split_first() {
+    None => return Some(value),
+    Some(split) => split,
+  };
+
+  match (first, value) {
+    (Segment::Index(index), Value::Array(items)) => {
+      items.get(*index).and_then(|item| lookup(item, rest))
+    }
+    (Segment::Key(key), Value::Object(map)) => {
+      if let Some(found) = map.get(key).and_then(|child| lookup(child, rest)) {
+        return Some(found);
+      }
+      // Some loggers write nested fields as flat keys containing dots, like
+      // `{"log.level": "info"}`: try the following keys joined together.
+      let mut joined = key.clone();
+      for (position, segment) in rest.iter().enumerate() {
+        match segment {
+          Segment::Key(next) => {
+            joined.push('.');
+            joined.push_str(next);
+            let found = map
+              .get(&joined)
+              .and_then(|child| lookup(child, &rest[position + 1..]));
+            if found.is_some() {
+              return found;
+            }
+          }
+          Segment::Index(_) => break,
+        }
+      }
+      None
+    }
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn keys(keys: &[&str]) -> Vec<Segment> {
+    keys
+      .iter()
+      .map(|key| Segment::Key(key.to_string()))
+      .collect()
+  }
+
+  fn segments(text: &str) -> Vec<Segment> {
+    FieldPath::parse(text).unwrap().segments
+  }
+
+  #[test]
+  fn test_parse_path() {
+    assert_eq!(segments("level"), keys(&["level"]));
+    assert_eq!(segments("@timestamp"), keys(&["@timestamp"]));
+    assert_eq!(
+      segments("instant.epochSecond"),
+      keys(&["instant", "epochSecond"])
+    );
+    assert_eq!(
+      segments("tags[0]"),
+      vec![Segment::Key("tags".to_string()), Segment::Index(0)]
+    );
+    assert_eq!(
+      segments("a[1][2].b"),
+      vec![
+        Segment::Key("a".to_string()),
+        Segment::Index(1),
+        Segment::Index(2),
+        Segment::Key("b".to_string())
+      ]
+    );
+    assert_eq!(
+      segments(r"logging\.googleapis\.com/trace"),
+      keys(&["logging.googleapis.com/trace"])
+    );
+    assert_eq!(
+      segments(r#"labels["app.kubernetes.io/name"]"#),
+      keys(&["labels", "app.kubernetes.io/name"])
+    );
+  }
+
+  #[test]
+  fn test_parse_invalid_path() {
+    assert!(FieldPath::parse("").is_err());
+    assert!(FieldPath::parse("a..b").is_err());
+    assert!(FieldPath::parse("a.").is_err());
+    assert!(FieldPath::parse("tags[x]").is_err());
+    assert!(FieldPath::parse("tags[0").is_err());
+    assert!(FieldPath::parse(r#"a["b"#).is_err());
+    assert!(FieldPath::parse("a\\").is_err());
+  }
+
+  #[test]
+  fn test_get() {
+    let entry = json!({
+      "@timestamp": "2020-01-13T12:34:01.740Z",
+      "mdc": { "requestId": "abc" },
+      "tags": ["first", { "name": "second" }],
+      "log.level": "info",
+      "log": { "logger": "main" },
+      "logging.googleapis.com/trace": "t-1"
+    });
+    let get = |text: &str| FieldPath::parse(text).unwrap().get(&entry).cloned();
+
+    assert_eq!(get("mdc.requestId"), Some(json!("abc")));
+    assert_eq!(get("@timestamp"), Some(json!("2020-01-13T12:34:01.740Z")));
+    assert_eq!(get("tags[0]"), Some(json!("first")));
+    assert_eq!(get("tags[1].name"), Some(json!("second")));
+    assert_eq!(get("tags[2]"), None);
+    assert_eq!(get("log.level"), Some(json!("info")));
+    assert_eq!(get("log.logger"), Some(json!("main")));
+    assert_eq!(get(r"logging\.googleapis\.com/trace"), Some(json!("t-1")));
+    assert_eq!(get("logging.googleapis.com/trace"), Some(json!("t-1")));
+    assert_eq!(get("mdc.missing"), None);
+    assert_eq!(get("mdc[0]"), None);
+  }
+}
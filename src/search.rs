@@ -0,0 +1,139 @@
+use crate::output::ANSI_REGEX;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use std::str::FromStr;
+
+// Reverse video, switched off on its own so the surrounding colors stay.
+const HIGHLIGHT_START: &str = "\x1b[7m";
+const HIGHLIGHT_END: &str = "\x1b[27m";
+
+/// A `--search`: plain text, or a regex written as `/regex/` (`/regex/i` to
+/// ignore case), looked for anywhere in the line.
+#[derive(Debug, Clone)]
+pub struct Search(Regex);
+
+impl PartialEq for Search {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.as_str() == other.0.as_str()
+  }
+}
+
+impl Search {
+  /// Matches the raw line, or any string value of the parsed line (nested
+  /// ones included), as the raw line holds them escaped.
+  pub fn is_match(&self, line: &str, entry: Option<&Value>) -> bool {
+    self.0.is_match(line) || entry.is_some_and(|entry| self.is_match_in_strings(entry))
+  }
+
+  fn is_match_in_strings(&self, value: &Value) -> bool {
+    match value {
+      Value::String(text) => self.0.is_match(text),
+      Value::Array(values) => values.iter().any(|value| self.is_match_in_strings(value)),
+      Value::Object(map) => map.values().any(|value| self.is_match_in_strings(value)),
+      _ => false,
+    }
+  }
+
+  /// Highlights the matches in a formatted line. Color codes are left
+  /// untouched, the matches are only looked for in the text between them.
+  pub fn highlight(&self, formatted: &str) -> String {
+    let mut highlighted = String::with_capacity(formatted.len());
+    let mut start = 0;
+    for code in ANSI_REGEX.find_iter(formatted) {
+      self.highlight_text(&formatted[start..code.start()], &mut highlighted);
+      highlighted.push_str(code.as_str());
+      start = code.end();
+    }
+    self.highlight_text(&formatted[start..], &mut highlighted);
+    highlighted
+  }
+
+  fn highlight_text(&self, text: &str, highlighted: &mut String) {
+    let mut start = 0;
+    for found in self.0.find_iter(text).filter(|found| !found.is_empty()) {
+      highlighted.push_str(&text[start..found.start()]);
+      highlighted.push_str(HIGHLIGHT_START);
+      highlighted.push_str(found.as_str());
+      highlighted.push_str(HIGHLIGHT_END);
+      start = found.end();
+    }
+    highlighted.push_str(&text[start..]);
+  }
+}
+
+impl FromStr for Search {
+  type Err = String;
+
+  fn from_str(text: &str) -> Result<Self, Self::Err> {
+    let (pattern, ignore_case) = if text.len() > 2 && text.starts_with('/') && text.ends_with('/') {
+      (&text[1..text.len() - 1], false)
+    } else if text.len() > 3 && text.starts_with('/') && text.ends_with("/i") {
+      (&text[1..text.len() - 2], true)
+    } else if text.is_empty() {
+      return Err("The search can't be empty".to_string());
+    } else {
+      return Ok(Search(Regex::new(&regex::escape(text)).unwrap()));
+    };
+    RegexBuilder::new(pattern)
+      .case_insensitive(ignore_case)
+      .build()
+      .map(Search)
+      .map_err(|e| format!("Invalid regex in search '{text}': {e}"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn search(text: &str) -> Search {
+    text.parse().unwrap()
+  }
+
+  #[test]
+  fn test_parse_search() {
+    assert_eq!(
+      search("a.b (c)"),
+      Search(Regex::new(r"a\.b \(c\)").unwrap())
+    );
+    assert_eq!(
+      search(r"/node \d+/"),
+      Search(Regex::new(r"node \d+").unwrap())
+    );
+    assert!(search("/timeout/i").is_match("Timeout", None));
+    assert!(!search("/timeout/").is_match("Timeout", None));
+    // Not a regex: a lone slash or a path.
+    assert!(search("/").is_match("a/b", None));
+    assert!(search("/var/log").is_match("/var/log/app.log", None));
+    assert!("/(unclosed/".parse::<Search>().is_err());
+    assert!("".parse::<Search>().is_err());
+  }
+
+  #[test]
+  fn test_search_matches_line_or_string_values() {
+    let line =
+      r#"{"message":"say \"hi\"","mdc":{"requestId":"GB8218"},"tags":["kafka"],"status":503}"#;
+    let entry: Value = serde_json::from_str(line).unwrap();
+    assert!(search(r#"say "hi""#).is_match(line, Some(&entry)));
+    assert!(search("GB8218").is_match(line, Some(&entry)));
+    assert!(search("kafka").is_match(line, Some(&entry)));
+    assert!(search("503").is_match(line, Some(&entry)));
+    assert!(search("requestId").is_match(line, Some(&entry)));
+    assert!(!search("flink").is_match(line, Some(&entry)));
+    assert!(search("/^Not JSON/").is_match("Not JSON at all", None));
+  }
+
+  #[test]
+  fn test_highlight_keeps_colors() {
+    assert_eq!(
+      search("/o+/").highlight("\x1b[31mfoo bar boo\x1b[0m"),
+      "\x1b[31mf\x1b[7moo\x1b[27m bar b\x1b[7moo\x1b[27m\x1b[0m"
+    );
+    // The color codes themselves are never highlighted.
+    assert_eq!(
+      search("31").highlight("\x1b[31m31\x1b[0m"),
+      "\x1b[31m\x1b[7m31\x1b[27m\x1b[0m"
+    );
+    assert_eq!(search("/x*/").highlight("abc"), "abc");
+  }
+}
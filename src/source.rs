@@ -0,0 +1,555 @@
+use crate::container::*;
+use crate::format::timestamp_of;
+use crate::input::*;
+use crate::logfmt::parse_logfmt;
+use crate::multiline::*;
+use crate::time::parse_timestamp;
+use chrono::prelude::*;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+
+pub struct LogRecord {
+  /// Index of the input the line was read from.
+  pub source: usize,
+  pub line: String,
+  /// Set when the line is a JSON object, or a logfmt line.
+  pub json: Option<Value>,
+  /// The text logged before the JSON on the line, like a syslog header.
+  pub prefix: Option<String>,
+  /// Set when the line was logged inside a container.
+  pub container: Option<ContainerMeta>,
+}
+
+impl LogRecord {
+  pub fn parse(source: usize, line: String) -> Self {
+    let json = serde_json::from_str::<Value>(&line)
+      .ok()
+      .filter(Value::is_object)
+      .or_else(|| parse_logfmt(&line));
+    let (prefix, json) = match json {
+      Some(json) => (None, Some(json)),
+      None => match embedded_object(&line) {
+        Some((prefix, json)) => (Some(prefix), Some(json)),
+        None => (None, None),
+      },
+    };
+    LogRecord {
+      source,
+      line,
+      json,
+      prefix,
+      container: None,
+    }
+  }
+
+  /// The line logged inside a container, with the stream and the time from
+  /// the runtime.
+  fn from_container(source: usize, container_line: ContainerLine) -> Self {
+    LogRecord {
+      container: Some(ContainerMeta {
+        stream: container_line.stream,
+        time: container_line.time,
+      }),
+      ..LogRecord::parse(source, container_line.payload)
+    }
+  }
+
+  /// The JSON of the line as the filters see it: what was read around it is
+  /// added as reserved fields (`_prefix`, `_stream` and `_container_time`),
+  /// over the fields of the same name. The JSON itself is left unchanged.
+  pub fn fields(&self) -> Option<Cow<'_, Value>> {
+    let entry = self.json.as_ref()?;
+    let mut reserved = Vec::new();
+    if let Some(prefix) = &self.prefix {
+      reserved.push(("_prefix", prefix));
+    }
+    if let Some(container) = &self.container {
+      reserved.push(("_stream", &container.stream));
+      reserved.push(("_container_time", &container.time));
+    }
+    match entry {
+      Value::Object(map) if !reserved.is_empty() => {
+        let mut map = map.clone();
+        for (key, value) in reserved {
+          map.insert(key.to_string(), Value::String(value.clone()));
+        }
+        Some(Cow::Owned(Value::Object(map)))
+      }
+      _ => Some(Cow::Borrowed(entry)),
+    }
+  }
+
+  /// The time the line was logged at, or else the time the container runtime
+  /// wrote it at.
+  pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+    self.json.as_ref().and_then(timestamp_of).or_else(|| {
+      self
+        .container
+        .as_ref()
+        .and_then(|container| parse_timestamp(&container.time))
+    })
+  }
+}
+
+// Only this many '{' are tried as the start of the object, so that long lines
+// of text aren't parsed again and again.
+const MAX_EMBEDDED_OBJECT_STARTS: usize = 8;
+
+// A JSON object logged after some text, like
+// `2024-05-01 12:00:00 host app[123]: {"level":"info",...}`, with that text.
+fn embedded_object(line: &str) -> Option<(String, Value)> {
+  // The object ends the line.
+  if !line.trim_end().ends_with('}') {
+    return None;
+  }
+  line
+    .match_indices('{')
+    .filter(|(index, _)| *index > 0)
+    .take(MAX_EMBEDDED_OBJECT_STARTS)
+    .find_map(|(index, _)| match serde_json::from_str(&line[index..]) {
+      Ok(object @ Value::Object(_)) => Some((line[..index].trim_end().to_string(), object)),
+      _ => None,
+    })
+}
+
+pub type Records = Box<dyn Iterator<Item = io::Result<LogRecord>>>;
+
+pub fn read_records(source: usize, lines: Lines) -> Records {
+  Box::new(RecordReader::new(source, Input::Lines(lines)))
+}
+
+/// Like `read_records`, for a followed file.
+pub fn read_followed_records(source: usize, reader: FollowReader) -> Records {
+  Box::new(RecordReader::new(source, Input::Follow(reader)))
+}
+
+enum Input {
+  Lines(Lines),
+  // Never ends: a document left incomplete is given up on when no line comes
+  // for a while.
+  Follow(FollowReader),
+}
+
+enum NextLine {
+  Line(String),
+  Idle,
+  End,
+}
+
+impl Input {
+  fn next_line(&mut self) -> io::Result<NextLine> {
+    match self {
+      Input::Lines(lines) => match lines.next() {
+        None => Ok(NextLine::End),
+        Some(line) => line.map(NextLine::Line),
+      },
+      Input::Follow(reader) => Ok(match reader.poll_line()? {
+        None => NextLine::Idle,
+        Some(line) => NextLine::Line(line),
+      }),
+    }
+  }
+}
+
+/// Reads the records of one input.
+struct RecordReader {
+  source: usize,
+  input: Input,
+  unwrapper: Unwrapper,
+  assembler: Assembler,
+  // A line can hold several records, like an array of them.
+  pending: VecDeque<LogRecord>,
+  ended: bool,
+}
+
+impl RecordReader {
+  fn new(source: usize, input: Input) -> Self {
+    RecordReader {
+      source,
+      input,
+      unwrapper: Unwrapper::default(),
+      assembler: Assembler::default(),
+      pending: VecDeque::new(),
+      ended: false,
+    }
+  }
+
+  fn read(&mut self, unwrapped: Unwrapped) {
+    match unwrapped {
+      Unwrapped::Plain(line) => {
+        let assembled = self.assembler.push(line);
+        self.read_assembled(assembled);
+      }
+      Unwrapped::Container(container_line) => self
+        .pending
+        .push_back(LogRecord::from_container(self.source, container_line)),
+    }
+  }
+
+  fn read_assembled(&mut self, assembled: Vec<Assembled>) {
+    for document in assembled {
+      self.pending.push_back(match document {
+        Assembled::Object(json, line) => LogRecord {
+          source: self.source,
+          line,
+          json: Some(json),
+          prefix: None,
+          container: None,
+        },
+        Assembled::Plain(line) => LogRecord::parse(self.source, line),
+      });
+    }
+  }
+}
+
+impl Iterator for RecordReader {
+  type Item = io::Result<LogRecord>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(record) = self.pending.pop_front() {
+        return Some(Ok(record));
+      }
+      if self.ended {
+        return None;
+      }
+      match self.input.next_line() {
+        Ok(NextLine::End) => {
+          self.ended = true;
+          while let Some(unwrapped) = self.unwrapper.flush() {
+            self.read(unwrapped);
+          }
+          let assembled = self.assembler.flush();
+          self.read_assembled(assembled);
+        }
+        Ok(NextLine::Idle) => {
+          let assembled = self.assembler.flush();
+          if assembled.is_empty() {
+            thread::sleep(FOLLOW_POLL_INTERVAL);
+          }
+          self.read_assembled(assembled);
+        }
+        Err(e) => return Some(Err(e)),
+        Ok(NextLine::Line(line)) => {
+          if let Some(unwrapped) = self.unwrapper.unwrap(line) {
+            self.read(unwrapped);
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Opens all the inputs (stdin if there are none) and merges their lines.
+pub fn open_records(paths: &[String], follow: bool) -> io::Result<Records> {
+  if follow {
+    return follow_records(paths);
+  }
+  match paths {
+    [] => Ok(read_records(0, open_lines(None)?)),
+    [path] => Ok(read_records(0, open_lines(Some(path))?)),
+    _ => {
+      let sources = paths
+        .iter()
+        .enumerate()
+        .map(|(source, path)| Ok(read_records(source, open_lines(Some(path))?)))
+        .collect::<io::Result<Vec<Records>>>()?;
+      Ok(Box::new(MergeByTimestamp::new(sources)))
+    }
+  }
+}
+
+/// Followed files never end, so their lines can't be sorted: they are shown as
+/// they arrive.
+fn follow_records(paths: &[String]) -> io::Result<Records> {
+  let readers = paths
+    .iter()
+    .map(open_follow_reader)
+    .collect::<io::Result<Vec<FollowReader>>>()?;
+
+  if readers.len() == 1 {
+    let reader = readers.into_iter().next().unwrap();
+    return Ok(read_followed_records(0, reader));
+  }
+
+  let (sender, receiver) = mpsc::channel();
+  for (source, reader) in readers.into_iter().enumerate() {
+    let sender = sender.clone();
+    thread::spawn(move || {
+      for record in read_followed_records(source, reader) {
+        if sender.send(record).is_err() {
+          return;
+        }
+      }
+    });
+  }
+  Ok(Box::new(receiver.into_iter()))
+}
+
+/// Interleaves the records of several inputs by timestamp. Each input is
+/// expected to be sorted already. Lines without a timestamp (non-JSON lines,
+/// unknown formats) stay right after the line preceding them in their input.
+pub struct MergeByTimestamp {
+  sources: Vec<Records>,
+  heads: Vec<Option<(LogRecord, Option<DateTime<Utc>>)>>,
+  last_timestamps: Vec<Option<DateTime<Utc>>>,
+}
+
+impl MergeByTimestamp {
+  pub fn new(sources: Vec<Records>) -> Self {
+    let count = sources.len();
+    MergeByTimestamp {
+      sources,
+      heads: (0..count).map(|_| None).collect(),
+      last_timestamps: vec![None; count],
+    }
+  }
+
+  fn fill_heads(&mut self) -> io::Result<()> {
+    for (index, source) in self.sources.iter_mut().enumerate() {
+      if self.heads[index].is_some() {
+        continue;
+      }
+      if let Some(record) = source.next() {
+        let record = record?;
+        let timestamp = record.timestamp().or(self.last_timestamps[index]);
+        self.heads[index] = Some((record, timestamp));
+      }
+    }
+    Ok(())
+  }
+}
+
+impl Iterator for MergeByTimestamp {
+  type Item = io::Result<LogRecord>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if let Err(e) = self.fill_heads() {
+      return Some(Err(e));
+    }
+
+    // `min_by_key` keeps the first of equal elements, so ties go to the
+    // input given first.
+    let index = self
+      .heads
+      .iter()
+      .enumerate()
+      .filter_map(|(index, head)| head.as_ref().map(|(_, timestamp)| (index, *timestamp)))
+      .min_by_key(|(_, timestamp)| *timestamp)
+      .map(|(index, _)| index)?;
+
+    let (record, timestamp) = self.heads[index].take().unwrap();
+    self.last_timestamps[index] = timestamp;
+    Some(Ok(record))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn records(source: usize, lines: &[&str]) -> Records {
+    let lines = lines
+      .iter()
+      .map(|line| Ok(line.to_string()))
+      .collect::<Vec<io::Result<String>>>();
+    read_records(source, Box::new(lines.into_iter()))
+  }
+
+  fn merged(sources: Vec<Records>) -> Vec<(usize, String)> {
+    MergeByTimestamp::new(sources)
+      .map(|record| record.unwrap())
+      .map(|record| (record.source, record.line))
+      .collect()
+  }
+
+  #[test]
+  fn container_lines_are_unwrapped() {
+    let read = records(
+      0,
+      &[
+        r#"2024-01-01T00:00:00Z stdout P {"level":"info","#,
+        r#"2024-01-01T00:00:00Z stdout F "message":"started"}"#,
+        r#"{"log":"Listening on 8080\n","stream":"stderr","time":"2024-01-01T00:00:01Z"}"#,
+      ],
+    )
+    .map(|record| record.unwrap())
+    .collect::<Vec<LogRecord>>();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].line, r#"{"level":"info","message":"started"}"#);
+    assert_eq!(
+      read[0].json,
+      Some(json!({ "level": "info", "message": "started" }))
+    );
+    assert_eq!(
+      read[0].container,
+      Some(ContainerMeta {
+        stream: "stdout".to_string(),
+        time: "2024-01-01T00:00:00Z".to_string()
+      })
+    );
+    assert_eq!(read[0].timestamp(), parse_timestamp("2024-01-01T00:00:00Z"));
+    assert_eq!(read[1].line, "Listening on 8080");
+    assert_eq!(read[1].json, None);
+    assert_eq!(
+      read[1]
+        .container
+        .as_ref()
+        .map(|container| container.stream.as_str()),
+      Some("stderr")
+    );
+  }
+
+  #[test]
+  fn json_after_a_prefix_is_parsed() {
+    let parse = |line: &str| {
+      let record = LogRecord::parse(0, line.to_string());
+      (record.prefix, record.json)
+    };
+    assert_eq!(
+      parse(r#"2024-05-01 12:00:00 host app[123]: {"level":"info","message":"hi"}"#),
+      (
+        Some("2024-05-01 12:00:00 host app[123]:".to_string()),
+        Some(json!({ "level": "info", "message": "hi" }))
+      )
+    );
+    // The first '{' isn't always the start of the object.
+    assert_eq!(
+      parse(r#"app{eu-1} {"message":"hi"} "#),
+      (
+        Some("app{eu-1}".to_string()),
+        Some(json!({ "message": "hi" }))
+      )
+    );
+    // A `_prefix` logged by the application is left alone.
+    assert_eq!(
+      parse(r#"app: {"message":"hi","_prefix":"mine"}"#),
+      (
+        Some("app:".to_string()),
+        Some(json!({ "message": "hi", "_prefix": "mine" }))
+      )
+    );
+    assert_eq!(
+      parse(r#"{"message":"hi"}"#),
+      (None, Some(json!({ "message": "hi" })))
+    );
+    assert_eq!(parse(r#"prefix: {"message":"hi"} suffix"#), (None, None));
+    assert_eq!(parse("prefix: [1, 2]"), (None, None));
+    assert_eq!(parse("Exception in thread main {"), (None, None));
+  }
+
+  #[test]
+  fn pretty_printed_documents_are_read() {
+    let read = records(
+      0,
+      &[
+        "Starting",
+        "[",
+        r#"  {"message": "a"},"#,
+        r#"  {"message": "b"}"#,
+        "]",
+        "{",
+        r#"  "message": "c""#,
+        "}",
+      ],
+    )
+    .map(|record| record.unwrap())
+    .map(|record| (record.line, record.json))
+    .collect::<Vec<(String, Option<Value>)>>();
+    assert_eq!(
+      read,
+      vec![
+        ("Starting".to_string(), None),
+        (
+          r#"{"message": "a"}"#.to_string(),
+          Some(json!({"message": "a"}))
+        ),
+        (
+          r#"{"message": "b"}"#.to_string(),
+          Some(json!({"message": "b"}))
+        ),
+        (
+          "{\n  \"message\": \"c\"\n}".to_string(),
+          Some(json!({"message": "c"}))
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn followed_documents_are_given_up_on_when_idle() {
+    let path = std::env::temp_dir().join(format!("jaslog-{}-idle.log", std::process::id()));
+    std::fs::write(&path, "{\n").unwrap();
+    let mut read = read_followed_records(0, FollowReader::open(&path).unwrap());
+    let record = read.next().unwrap().unwrap();
+    assert_eq!((record.line, record.json), ("{".to_string(), None));
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn merge_interleaves_by_timestamp() {
+    let first = records(
+      0,
+      &[
+        r#"{"@timestamp": "2020-01-13T12:34:01.000Z", "message": "a1"}"#,
+        r#"{"@timestamp": "2020-01-13T12:34:03.000Z", "message": "a2"}"#,
+      ],
+    );
+    let second = records(
+      1,
+      &[
+        r#"{"timestamp": "2020-01-13T12:34:02", "message": "b1"}"#,
+        r#"{"timestamp": "2020-01-13T12:34:04", "message": "b2"}"#,
+      ],
+    );
+
+    let sources = merged(vec![first, second])
+      .into_iter()
+      .map(|(source, _)| source)
+      .collect::<Vec<usize>>();
+    assert_eq!(sources, vec![0, 1, 0, 1]);
+  }
+
+  #[test]
+  fn merge_keeps_untimed_lines_after_their_predecessor() {
+    let first = records(
+      0,
+      &[
+        r#"{"time": "2020-01-13T12:34:01Z", "message": "a1"}"#,
+        "Exception in thread main",
+        r#"{"time": "2020-01-13T12:34:05Z", "message": "a2"}"#,
+      ],
+    );
+    let second = records(
+      1,
+      &[
+        "starting up",
+        r#"{"time": "2020-01-13T12:34:03Z", "message": "b1"}"#,
+      ],
+    );
+
+    assert_eq!(
+      merged(vec![first, second]),
+      vec![
+        (1, "starting up".to_string()),
+        (
+          0,
+          r#"{"time": "2020-01-13T12:34:01Z", "message": "a1"}"#.to_string()
+        ),
+        (0, "Exception in thread main".to_string()),
+        (
+          1,
+          r#"{"time": "2020-01-13T12:34:03Z", "message": "b1"}"#.to_string()
+        ),
+        (
+          0,
+          r#"{"time": "2020-01-13T12:34:05Z", "message": "a2"}"#.to_string()
+        ),
+      ]
+    );
+  }
+}
@@ -0,0 +1,225 @@
+use crate::source::LogRecord;
+use chrono::prelude::*;
+use chrono::Duration;
+use regex::Regex;
+
+// Timestamps without an offset are taken as UTC.
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+const OFFSET_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z"];
+// Also accepted for `--since` and `--until`, on top of the formats above.
+const SHORT_DATETIME_FORMATS: [&str; 2] = ["%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"];
+const TIME_FORMATS: [&str; 2] = ["%H:%M:%S%.f", "%H:%M"];
+
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+  let text = text.trim();
+  if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+    return Some(datetime.with_timezone(&Utc));
+  }
+  for format in OFFSET_FORMATS.iter() {
+    if let Ok(datetime) = DateTime::parse_from_str(text, format) {
+      return Some(datetime.with_timezone(&Utc));
+    }
+  }
+  for format in NAIVE_FORMATS.iter() {
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+      return Some(datetime.and_utc());
+    }
+  }
+  None
+}
+
+pub fn timestamp_from_epoch(epoch_second: i64, nano_of_second: u32) -> Option<DateTime<Utc>> {
+  DateTime::from_timestamp(epoch_second, nano_of_second)
+}
+
+/// Parses the value of `--since` or `--until`: a full timestamp, a date, a
+/// time of today (`10:52`) or a duration before now (`15m ago`). Like in the
+/// logs, dates and times without an offset are taken as UTC.
+pub fn parse_time_bound(text: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+  let text = text.trim();
+  if text == "now" {
+    return Ok(now);
+  }
+  if let Some(timestamp) = parse_timestamp(text) {
+    return Ok(timestamp);
+  }
+  for format in SHORT_DATETIME_FORMATS.iter() {
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, format) {
+      return Ok(datetime.and_utc());
+    }
+  }
+  if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+    return Ok(date.and_time(NaiveTime::MIN).and_utc());
+  }
+  for format in TIME_FORMATS.iter() {
+    if let Ok(time) = NaiveTime::parse_from_str(text, format) {
+      return Ok(now.date_naive().and_time(time).and_utc());
+    }
+  }
+  if let Some(duration) = parse_duration_ago(text) {
+    return Ok(now - duration);
+  }
+  Err(format!(
+    "Can't parse time '{text}', expected a timestamp (2024-05-01T10:52:00Z), \
+     a date (2024-05-01), a time (10:52) or a duration (15m ago)"
+  ))
+}
+
+// `15m ago`, `1h30m ago`, `2 days ago` or just `15m`.
+fn parse_duration_ago(text: &str) -> Option<Duration> {
+  lazy_static! {
+    static ref DURATION_REGEX: Regex = Regex::new(r"^(?:\d+\s*[a-z]+\s*)+?(?:ago)?$").unwrap();
+    static ref PART_REGEX: Regex = Regex::new(r"(\d+)\s*([a-z]+)").unwrap();
+  }
+
+  let text = text.to_lowercase();
+  if !DURATION_REGEX.is_match(&text) {
+    return None;
+  }
+  let parts = PART_REGEX
+    .captures_iter(text.trim_end_matches("ago"))
+    .collect::<Vec<_>>();
+  if parts.is_empty() {
+    return None;
+  }
+  parts
+    .iter()
+    .map(|caps| {
+      let amount = caps.get(1).unwrap().as_str().parse::<i64>().ok()?;
+      match caps.get(2).unwrap().as_str() {
+        "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(amount),
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount),
+        "h" | "hour" | "hours" => Duration::try_hours(amount),
+        "d" | "day" | "days" => Duration::try_days(amount),
+        "w" | "week" | "weeks" => Duration::try_weeks(amount),
+        _ => None,
+      }
+    })
+    .try_fold(Duration::zero(), |total, part| total.checked_add(&part?))
+}
+
+/// Bounds given with `--since` and `--until`, both included.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct TimeRange {
+  pub since: Option<DateTime<Utc>>,
+  pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+  /// When the range is bounded, lines without a recognizable timestamp don't
+  /// pass.
+  pub fn passes(&self, record: &LogRecord) -> bool {
+    if self.since.is_none() && self.until.is_none() {
+      return true;
+    }
+    match record.timestamp() {
+      None => false,
+      Some(timestamp) => {
+        self.since.is_none_or(|since| timestamp >= since)
+          && self.until.is_none_or(|until| timestamp <= until)
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::Value;
+
+  fn utc(text: &str) -> Option<DateTime<Utc>> {
+    Some(
+      DateTime::parse_from_rfc3339(text)
+        .unwrap()
+        .with_timezone(&Utc),
+    )
+  }
+
+  #[test]
+  fn test_parse_timestamp() {
+    assert_eq!(
+      parse_timestamp("2020-01-13T12:34:01.740Z"),
+      utc("2020-01-13T12:34:01.740Z")
+    );
+    assert_eq!(
+      parse_timestamp("2021-06-03T14:50:07.420+02:00"),
+      utc("2021-06-03T12:50:07.420Z")
+    );
+    assert_eq!(
+      parse_timestamp("2021-06-03T14:50:07.420+0200"),
+      utc("2021-06-03T12:50:07.420Z")
+    );
+    assert_eq!(
+      parse_timestamp("2019-12-18T10:55:50.000345"),
+      utc("2019-12-18T10:55:50.000345Z")
+    );
+    assert_eq!(
+      parse_timestamp("2019-12-18 10:55:50"),
+      utc("2019-12-18T10:55:50Z")
+    );
+    assert_eq!(parse_timestamp("yesterday"), None);
+  }
+
+  fn now() -> DateTime<Utc> {
+    utc("2024-05-01T12:00:00Z").unwrap()
+  }
+
+  fn bound(text: &str) -> Option<DateTime<Utc>> {
+    parse_time_bound(text, now()).ok()
+  }
+
+  #[test]
+  fn test_parse_time_bound() {
+    assert_eq!(
+      bound("2024-04-30T10:52:00+02:00"),
+      utc("2024-04-30T08:52:00Z")
+    );
+    assert_eq!(
+      bound("2024-04-30 10:52:13.5"),
+      utc("2024-04-30T10:52:13.5Z")
+    );
+    assert_eq!(bound("2024-04-30T10:52"), utc("2024-04-30T10:52:00Z"));
+    assert_eq!(bound("2024-04-30"), utc("2024-04-30T00:00:00Z"));
+    assert_eq!(bound("10:52"), utc("2024-05-01T10:52:00Z"));
+    assert_eq!(bound("10:52:30"), utc("2024-05-01T10:52:30Z"));
+    assert_eq!(bound("now"), utc("2024-05-01T12:00:00Z"));
+    assert_eq!(bound("15m ago"), utc("2024-05-01T11:45:00Z"));
+    assert_eq!(bound("15m"), utc("2024-05-01T11:45:00Z"));
+    assert_eq!(bound("1h30m ago"), utc("2024-05-01T10:30:00Z"));
+    assert_eq!(bound("2 days ago"), utc("2024-04-29T12:00:00Z"));
+    assert_eq!(bound("45 seconds ago"), utc("2024-05-01T11:59:15Z"));
+    assert_eq!(bound("yesterday"), None);
+    assert_eq!(bound("15 parsecs ago"), None);
+    assert_eq!(bound("ago"), None);
+    assert_eq!(bound("15 ago"), None);
+  }
+
+  #[test]
+  fn test_time_range() {
+    let range = TimeRange {
+      since: utc("2019-12-18T10:52:00Z"),
+      until: utc("2019-12-18T10:55:00Z"),
+    };
+    let record = |entry: Value| LogRecord::parse(0, entry.to_string());
+    let at = |timestamp: &str| record(json!({ "time": timestamp, "message": "hi" }));
+
+    assert!(range.passes(&at("2019-12-18T10:52:00Z")));
+    assert!(range.passes(&at("2019-12-18T10:53:10.123Z")));
+    assert!(range.passes(&at("2019-12-18T10:55:00Z")));
+    assert!(!range.passes(&at("2019-12-18T10:51:59Z")));
+    assert!(!range.passes(&at("2019-12-18T10:55:00.001Z")));
+    assert!(!range.passes(&record(json!({ "message": "no timestamp" }))));
+    assert!(range.passes(&record(json!({
+      "instant": { "epochSecond": 1576666400, "nanoOfSecond": 0 },
+      "thread": "main",
+      "level": "INFO",
+      "loggerName": "a",
+      "message": "b",
+      "endOfBatch": false,
+      "loggerFqcn": "c",
+      "threadId": 1,
+      "threadPriority": 5
+    }))));
+    assert!(TimeRange::default().passes(&record(json!({ "message": "no timestamp" }))));
+  }
+}